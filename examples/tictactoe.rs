@@ -6,7 +6,7 @@ use num_traits::FloatConst;
 use rand::thread_rng;
 use rayon::prelude::*;
 
-use oxymcts::{GameTrait, mcts_uct_agent, random_agent};
+use oxymcts::{GameTrait, mcts_uct_agent, Outcome, random_agent};
 
 #[derive(Debug, Clone, Default)]
 struct TicTacToe {
@@ -126,8 +126,11 @@ impl GameTrait for TicTacToe {
         self.play(*m);
     }
 
-    fn get_winner(&self) -> Self::Player {
-        TicTacToe::get_winner(self)
+    fn outcome(&self) -> Outcome<Self::Player> {
+        match TicTacToe::get_winner(self) {
+            0 => Outcome::Draw,
+            winner => Outcome::Win(winner),
+        }
     }
 }
 
@@ -1,6 +1,6 @@
 use rand::prelude::{SliceRandom, ThreadRng};
 
-use crate::{DefaultMcts, GameTrait};
+use crate::{CancellationToken, DefaultMcts, FinalMove, GameTrait, SearchReport, StoppingRule};
 
 pub fn mcts_uct_agent<Game: GameTrait>(state: &Game, playouts: usize, c: f64) -> Game::Move {
     let mut mcts = DefaultMcts::new(state);
@@ -10,6 +10,45 @@ pub fn mcts_uct_agent<Game: GameTrait>(state: &Game, playouts: usize, c: f64) ->
     mcts.best_move(&c)
 }
 
+/// Same as `mcts_uct_agent` but the search can stop before the end of the budget, the report
+/// gives the number of iterations saved. The move returned is the one the stopping rule
+/// guarantees: the most visited child, or the best mean reward with `Confidence`.
+pub fn mcts_uct_agent_with_stopping<Game: GameTrait>(
+    state: &Game,
+    playouts: usize,
+    c: f64,
+    stopping_rule: &StoppingRule,
+) -> (Game::Move, SearchReport) {
+    let mut mcts = DefaultMcts::new(state);
+    let report = mcts.search(playouts, stopping_rule, &c, ());
+    let criterion = match stopping_rule {
+        StoppingRule::Never => FinalMove::TreePolicy,
+        StoppingRule::MostVisited => FinalMove::MostVisited,
+        StoppingRule::Confidence { .. } => FinalMove::MaxMeanReward,
+    };
+    (mcts.final_move(criterion, &c), report)
+}
+
+/// Same as `mcts_uct_agent` but the search can be interrupted by cancelling `token` from another
+/// thread, the best move found so far is returned. There is no move if the search was cancelled
+/// before exploring any.
+pub fn mcts_uct_agent_cancellable<Game: GameTrait>(
+    state: &Game,
+    playouts: usize,
+    c: f64,
+    token: &CancellationToken,
+) -> (Option<Game::Move>, SearchReport) {
+    let mut mcts = DefaultMcts::new(state);
+    mcts.set_cancellation_token(Some(token.clone()));
+    let report = mcts.search(playouts, &StoppingRule::Never, &c, ());
+    let best_move = if mcts.tree().root().has_children() {
+        Some(mcts.best_move(&c))
+    } else {
+        None
+    };
+    (best_move, report)
+}
+
 pub fn random_agent<Game: GameTrait>(state: &Game, thread_rng: &mut ThreadRng) -> Game::Move {
     state
         .legals_moves()
@@ -1,3 +1,4 @@
+use crate::eager::CachedState;
 use crate::mcts_node::MctsNode;
 use crate::traits::GameTrait;
 use ego_tree::Tree;
@@ -9,6 +10,15 @@ pub type Nat = u32;
 pub type MctsTree<T, M, R, A> = Tree<MctsNode<T, M, R, A>>;
 pub type LazyMctsTree<T, R, A> = Tree<LazyMctsNode<T, R, A>>;
 
+/// A node of a lazy tree only stores the move leading to it, `None` for the root. The historic
+/// of moves to the node is rebuilt by walking its parents, see `move_path`.
 pub type LazyMctsNode<T, Reward, A> =
-    MctsNode<Vec<<T as GameTrait>::Move>, <T as GameTrait>::Move, Reward, A>;
+    MctsNode<Option<<T as GameTrait>::Move>, <T as GameTrait>::Move, Reward, A>;
 
+/// A node of an eager tree stores the move leading to it and may cache its state.
+pub type EagerMctsNode<T, Reward, A> =
+    MctsNode<CachedState<T>, <T as GameTrait>::Move, Reward, A>;
+pub type EagerMctsTree<T, R, A> = Tree<EagerMctsNode<T, R, A>>;
+
+/// The moves played during a playout, with the player who played them.
+pub type PlayedMoves<T> = Vec<(<T as GameTrait>::Player, <T as GameTrait>::Move)>;
@@ -0,0 +1,402 @@
+use std::borrow::Cow;
+use std::fmt::Display;
+use std::marker::PhantomData;
+
+use num_traits::Zero;
+
+use crate::defaults::{DefaultBackProp, DefaultLazyTreePolicy, DefaultPlayout, DefaultUctEvaluator};
+use crate::evaluators::{ExplorationArgs, ExplorationEvaluator};
+use crate::memory::MemoryLimit;
+use crate::reward::{PlayerReward, RewardVec};
+use crate::stopping::{CancellationToken, SearchReport, StoppingRule};
+use crate::traits::{BackPropPolicy, Evaluator, GameTrait, LazyTreePolicy, Playout};
+use crate::tree_search::{FinalMove, LazyMcts};
+
+/// The default tree policy of a builder whose evaluator is `EV`.
+pub type BuilderTreePolicy<State, EV, A, R> =
+    DefaultLazyTreePolicy<State, ExplorationEvaluator<EV>, A, R>;
+
+/// A builder using the default tree policy of its evaluator.
+type WithDefaultTreePolicy<'a, State, PP, BP, EV, A, R> =
+    MctsBuilder<'a, State, BuilderTreePolicy<State, EV, A, R>, PP, BP, EV, A, R>;
+
+/// The settings of a builder which don't depend on its policies.
+#[derive(Clone, Debug)]
+struct Settings {
+    capacity: usize,
+    budget: usize,
+    stopping_rule: StoppingRule,
+    final_move: FinalMove,
+    seed: Option<u64>,
+    fpu: Option<f64>,
+    noise: f64,
+    memory_limit: Option<MemoryLimit>,
+    cancellation_token: Option<CancellationToken>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            capacity: 0,
+            budget: 1000,
+            stopping_rule: StoppingRule::Never,
+            final_move: FinalMove::MostVisited,
+            seed: None,
+            fpu: None,
+            noise: 0.,
+            memory_limit: None,
+            cancellation_token: None,
+        }
+    }
+}
+
+/// Configures a search and the arguments it runs with. `MctsBuilder::new` starts from UCT with
+/// an exploration constant of √2, random playouts, a budget of 1000 iterations and the most
+/// visited child as final move. The evaluator is wrapped in an `ExplorationEvaluator` to apply
+/// the first play urgency and the noise.
+pub struct MctsBuilder<'a, State, TP, PP, BP, EV, AddInfo, Reward>
+    where
+        State: GameTrait,
+        PP: Playout<State, EV::EvalResult>,
+        EV: Evaluator<State, Reward, AddInfo>,
+        AddInfo: Clone + Default,
+        Reward: Clone,
+{
+    root_state: Cow<'a, State>,
+    tree_policy: PhantomData<TP>,
+    playout_policy: PP,
+    playout_args: PP::Args,
+    backprop_policy: PhantomData<BP>,
+    evaluator_args: EV::Args,
+    settings: Settings,
+}
+
+impl<'a, State: GameTrait> WithDefaultTreePolicy<
+    'a,
+    State,
+    DefaultPlayout,
+    DefaultBackProp,
+    DefaultUctEvaluator,
+    (),
+    RewardVec<State::Player>,
+>
+{
+    pub fn new(root_state: &'a State) -> Self {
+        Self::from_root_state(Cow::Borrowed(root_state))
+    }
+
+    /// Same as `new` but the search owns its root state, see `LazyMcts::new_owned`.
+    pub fn new_owned(root_state: State) -> Self {
+        Self::from_root_state(Cow::Owned(root_state))
+    }
+
+    fn from_root_state(root_state: Cow<'a, State>) -> Self {
+        MctsBuilder {
+            root_state,
+            tree_policy: PhantomData,
+            playout_policy: DefaultPlayout,
+            playout_args: (),
+            backprop_policy: PhantomData,
+            evaluator_args: std::f64::consts::SQRT_2,
+            settings: Settings::default(),
+        }
+    }
+}
+
+impl<'a, State, TP, PP, BP, EV, A, R> MctsBuilder<'a, State, TP, PP, BP, EV, A, R>
+    where
+        State: GameTrait,
+        PP: Playout<State, EV::EvalResult>,
+        EV: Evaluator<State, R, A>,
+        A: Clone + Default,
+        R: Clone,
+{
+    /// Starts from any policies, the other types are inferred or given by the caller.
+    pub fn with_playout_policy(
+        root_state: &'a State,
+        playout_policy: PP,
+        playout_args: PP::Args,
+        evaluator_args: EV::Args,
+    ) -> Self {
+        MctsBuilder {
+            root_state: Cow::Borrowed(root_state),
+            tree_policy: PhantomData,
+            playout_policy,
+            playout_args,
+            backprop_policy: PhantomData,
+            evaluator_args,
+            settings: Settings::default(),
+        }
+    }
+
+    /// The exploration constant of evaluators like UCT.
+    pub fn exploration(mut self, c: f64) -> Self
+        where
+            EV: Evaluator<State, R, A, Args = f64>,
+    {
+        self.evaluator_args = c;
+        self
+    }
+
+    pub fn evaluator_args(mut self, evaluator_args: EV::Args) -> Self {
+        self.evaluator_args = evaluator_args;
+        self
+    }
+
+    pub fn playout_args(mut self, playout_args: PP::Args) -> Self {
+        self.playout_args = playout_args;
+        self
+    }
+
+    /// The maximum number of iterations of `MctsSearch::search`.
+    pub fn budget(mut self, budget: usize) -> Self {
+        self.settings.budget = budget;
+        self
+    }
+
+    pub fn stopping_rule(mut self, stopping_rule: StoppingRule) -> Self {
+        self.settings.stopping_rule = stopping_rule;
+        self
+    }
+
+    pub fn final_move(mut self, final_move: FinalMove) -> Self {
+        self.settings.final_move = final_move;
+        self
+    }
+
+    /// Seeds the random number generator of the search, see `LazyMcts::seed`.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.settings.seed = Some(seed);
+        self
+    }
+
+    /// The first play urgency, see `Evaluator::first_play_urgency`.
+    pub fn fpu(mut self, fpu: f64) -> Self {
+        self.settings.fpu = Some(fpu);
+        self
+    }
+
+    /// The magnitude of the uniform noise added to the values of the children.
+    pub fn noise(mut self, noise: f64) -> Self {
+        self.settings.noise = noise;
+        self
+    }
+
+    pub fn memory_limit(mut self, memory_limit: MemoryLimit) -> Self {
+        self.settings.memory_limit = Some(memory_limit);
+        self
+    }
+
+    pub fn cancellation_token(mut self, token: CancellationToken) -> Self {
+        self.settings.cancellation_token = Some(token);
+        self
+    }
+
+    /// The number of nodes allocated up front.
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.settings.capacity = capacity;
+        self
+    }
+
+    pub fn tree_policy<TP2>(self) -> MctsBuilder<'a, State, TP2, PP, BP, EV, A, R> {
+        MctsBuilder {
+            root_state: self.root_state,
+            tree_policy: PhantomData,
+            playout_policy: self.playout_policy,
+            playout_args: self.playout_args,
+            backprop_policy: PhantomData,
+            evaluator_args: self.evaluator_args,
+            settings: self.settings,
+        }
+    }
+
+    pub fn playout_policy<PP2: Playout<State, EV::EvalResult>>(
+        self,
+        playout_policy: PP2,
+        playout_args: PP2::Args,
+    ) -> MctsBuilder<'a, State, TP, PP2, BP, EV, A, R> {
+        MctsBuilder {
+            root_state: self.root_state,
+            tree_policy: PhantomData,
+            playout_policy,
+            playout_args,
+            backprop_policy: PhantomData,
+            evaluator_args: self.evaluator_args,
+            settings: self.settings,
+        }
+    }
+
+    pub fn backprop_policy<BP2>(self) -> MctsBuilder<'a, State, TP, PP, BP2, EV, A, R> {
+        MctsBuilder {
+            root_state: self.root_state,
+            tree_policy: PhantomData,
+            playout_policy: self.playout_policy,
+            playout_args: self.playout_args,
+            backprop_policy: PhantomData,
+            evaluator_args: self.evaluator_args,
+            settings: self.settings,
+        }
+    }
+
+    /// Changes the evaluator, the tree policy is reset to the default one for this evaluator, so
+    /// a custom tree policy must be set afterwards.
+    pub fn evaluator<EV2>(
+        self,
+        evaluator_args: EV2::Args,
+    ) -> WithDefaultTreePolicy<'a, State, PP, BP, EV2, A, R>
+        where
+            EV2: Evaluator<State, R, A, EvalResult = EV::EvalResult>,
+    {
+        MctsBuilder {
+            root_state: self.root_state,
+            tree_policy: PhantomData,
+            playout_policy: self.playout_policy,
+            playout_args: self.playout_args,
+            backprop_policy: PhantomData,
+            evaluator_args,
+            settings: self.settings,
+        }
+    }
+
+    /// Creates the search.
+    pub fn build(self) -> MctsSearch<'a, State, TP, PP, BP, ExplorationEvaluator<EV>, A, R>
+        where
+            TP: LazyTreePolicy<State, ExplorationEvaluator<EV>, A, R>,
+            BP: BackPropPolicy<Option<State::Move>, State::Move, R, A, EV::EvalResult>,
+            R: Zero + Display,
+    {
+        let settings = self.settings;
+        let mut mcts =
+            LazyMcts::from_root_state(self.root_state, settings.capacity, self.playout_policy);
+        if let Some(seed) = settings.seed {
+            mcts.seed(seed);
+        }
+        mcts.set_memory_limit(settings.memory_limit);
+        mcts.set_cancellation_token(settings.cancellation_token);
+        MctsSearch {
+            mcts,
+            evaluator_args: ExplorationArgs {
+                args: self.evaluator_args,
+                fpu: settings.fpu,
+                noise: settings.noise,
+            },
+            playout_args: self.playout_args,
+            budget: settings.budget,
+            stopping_rule: settings.stopping_rule,
+            final_move: settings.final_move,
+        }
+    }
+}
+
+/// A search built by `MctsBuilder`, it keeps the arguments of the search so it runs without
+/// them.
+pub struct MctsSearch<'a, State, TP, PP, BP, EV, AddInfo, Reward>
+    where
+        State: GameTrait,
+        TP: LazyTreePolicy<State, EV, AddInfo, Reward>,
+        PP: Playout<State, EV::EvalResult>,
+        BP: BackPropPolicy<Option<State::Move>, State::Move, Reward, AddInfo, EV::EvalResult>,
+        EV: Evaluator<State, Reward, AddInfo>,
+        AddInfo: Clone + Default,
+        Reward: Clone,
+{
+    mcts: LazyMcts<'a, State, TP, PP, BP, EV, AddInfo, Reward>,
+    evaluator_args: EV::Args,
+    playout_args: PP::Args,
+    budget: usize,
+    stopping_rule: StoppingRule,
+    final_move: FinalMove,
+}
+
+impl<'a, State, TP, PP, BP, EV, A, R> MctsSearch<'a, State, TP, PP, BP, EV, A, R>
+    where
+        State: GameTrait,
+        TP: LazyTreePolicy<State, EV, A, R>,
+        PP: Playout<State, EV::EvalResult>,
+        BP: BackPropPolicy<Option<State::Move>, State::Move, R, A, EV::EvalResult>,
+        EV: Evaluator<State, R, A>,
+        A: Clone + Default,
+        R: Clone + Zero + Display + PlayerReward<State::Player>,
+        PP::Args: Clone,
+{
+    /// Executes one iteration.
+    pub fn execute(&mut self) {
+        self.mcts.execute(&self.evaluator_args, self.playout_args.clone());
+    }
+
+    /// Searches with the budget and the stopping rule of the builder.
+    pub fn search(&mut self) -> SearchReport {
+        self.mcts.search(
+            self.budget,
+            &self.stopping_rule,
+            &self.evaluator_args,
+            self.playout_args.clone(),
+        )
+    }
+
+    /// The move chosen with the final move criterion of the builder.
+    pub fn best_move(&self) -> State::Move {
+        self.mcts.final_move(self.final_move, &self.evaluator_args)
+    }
+
+    /// Searches then returns the best move.
+    pub fn run(&mut self) -> State::Move {
+        self.search();
+        self.best_move()
+    }
+
+    /// Plays `m` at the root, see `LazyMcts::advance`.
+    pub fn advance(&mut self, m: &State::Move) -> bool
+        where
+            State::Move: PartialEq,
+    {
+        self.mcts.advance(m)
+    }
+
+    pub fn evaluator_args(&self) -> &EV::Args {
+        &self.evaluator_args
+    }
+
+    pub fn mcts(&self) -> &LazyMcts<'a, State, TP, PP, BP, EV, A, R> {
+        &self.mcts
+    }
+
+    pub fn mcts_mut(&mut self) -> &mut LazyMcts<'a, State, TP, PP, BP, EV, A, R> {
+        &mut self.mcts
+    }
+
+    pub fn into_inner(self) -> LazyMcts<'a, State, TP, PP, BP, EV, A, R> {
+        self.mcts
+    }
+}
+
+#[test]
+fn test_builder_searches_reproducibly() {
+    use crate::test_games::Guess;
+
+    let game = Guess(None);
+    let build = |seed| {
+        MctsBuilder::new(&game)
+            .exploration(1.)
+            .budget(300)
+            .seed(seed)
+            .fpu(1.)
+            .noise(0.01)
+            .build()
+    };
+    let run = |mut search: MctsSearch<_, _, _, _, _, _, _>| {
+        let m = search.run();
+        let visits: Vec<_> =
+            search.mcts().tree().root().children().map(|c| c.value().n_visits).collect();
+        (m, visits)
+    };
+    // The seed belongs to the search: the other searches run between its creation and its run,
+    // or the thread running it, don't change its result.
+    let (first, second) = (build(7), build(7));
+    let (m, visits) = run(first);
+    assert_eq!(m, 5);
+    assert_eq!(visits.iter().sum::<u32>(), 300);
+    let other_thread = std::thread::scope(|scope| scope.spawn(|| run(build(7))).join().unwrap());
+    assert_eq!(other_thread, (m, visits.clone()));
+    assert_eq!(run(second), (m, visits));
+}
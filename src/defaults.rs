@@ -1,14 +1,16 @@
 use std::marker::PhantomData;
-use std::ops::{Add, AddAssign, Div};
+use std::ops::{AddAssign, Mul};
 
-use ego_tree::{NodeId, NodeMut, Tree};
-use num_traits::{ToPrimitive, Zero};
-use rand::{Rng, thread_rng};
+use ego_tree::{NodeId, NodeMut, NodeRef, Tree};
+use num_traits::Zero;
+use rand::Rng;
 use rand::prelude::SliceRandom;
 
 use crate::{Evaluator, Nat, Num, uct_value};
-use crate::aliases::{LazyMctsNode, LazyMctsTree};
-use crate::mcts_node::MctsNode;
+use crate::aliases::{LazyMctsNode, LazyMctsTree, PlayedMoves};
+use crate::mcts_node::{move_path, MctsNode};
+use crate::reward::RewardVec;
+use crate::rng::search_rng;
 use crate::traits::{BackPropPolicy, GameTrait, LazyTreePolicy, Playout};
 
 /// A default backprop policy it will take the reward of the simulation and backkpropagate the
@@ -18,11 +20,12 @@ pub struct DefaultBackProp;
 impl<
     T: Clone,
     Move: Clone,
-    R: Add + AddAssign + Div + Clone + Zero + ToPrimitive,
+    R: AddAssign + Mul<Output=R> + Clone + Zero,
     A: Clone + Default,
 > BackPropPolicy<T, Move, R, A> for DefaultBackProp
 {
     fn backprop(tree: &mut Tree<MctsNode<T, Move, R, A>>, leaf: NodeId, reward: R) {
+        let squared_reward = reward.clone() * reward.clone();
         let root_id = tree.root().id();
         let mut current_node_id = leaf;
         // Update the branch
@@ -31,33 +34,148 @@ impl<
             node_to_update.value().n_visits += 1;
             node_to_update.value().sum_rewards =
                 node_to_update.value().sum_rewards.clone() + reward.clone();
+            node_to_update.value().sum_squared_rewards += squared_reward.clone();
             current_node_id = node_to_update.parent().unwrap().id();
         }
         // Update root
         let mut node_to_update = tree.get_mut(current_node_id).unwrap();
         node_to_update.value().n_visits += 1;
         node_to_update.value().sum_rewards += reward;
+        node_to_update.value().sum_squared_rewards += squared_reward;
     }
 }
 
 /// Simulating taking random moves a applying until the end.
+#[derive(Clone, Copy, Debug, Default)]
 pub struct DefaultPlayout;
 
-impl<T: GameTrait> Playout<T> for DefaultPlayout {
+impl<T: GameTrait, E> Playout<T, E> for DefaultPlayout {
     type Args = ();
 
-    fn playout(mut state: T, _args: ()) -> T {
+    fn playout(&mut self, mut state: T, _args: ()) -> (T, PlayedMoves<T>) {
+        let mut played = vec![];
         while !state.is_final() {
             let moves = state
                 .legals_moves();
-            let m = moves.choose(&mut thread_rng()) .unwrap();
+            let m = moves.choose(&mut search_rng()) .unwrap();
+            played.push((state.player_turn(), m.clone()));
             state.do_move(m);
         }
-        state
+        (state, played)
     }
 }
 
-/// Explores at least once each child node, before going deeper.
+/// Returns true if a child of `node` has a greater value than the first play urgency of the
+/// evaluator, the tree policy then goes down to a child instead of expanding `node`.
+pub(crate) fn has_urgent_child<State, EV, T, R, A>(
+    node: NodeRef<MctsNode<T, State::Move, R, A>>,
+    turn: &State::Player,
+    evaluator_args: &EV::Args,
+) -> bool
+    where
+        State: GameTrait,
+        EV: Evaluator<State, R, A>,
+        T: Clone,
+        R: Clone,
+        A: Clone + Default,
+{
+    let parent_visits = node.value().n_visits;
+    EV::first_play_urgency(evaluator_args).is_some_and(|fpu| {
+        node.children()
+            .any(|child| EV::eval_child(child.value(), turn, parent_visits, evaluator_args) > fpu)
+    })
+}
+
+/// Goes down the tree from `start` choosing at each node the child with the greatest `value` for
+/// the player to move, and returns the first node which can still be expanded with its state,
+/// unless one of its children is valued more than the `first_play_urgency`. `state` must be the
+/// state of `start`.
+pub(crate) fn select_by<State, R, A>(
+    tree: &LazyMctsTree<State, R, A>,
+    start: NodeId,
+    mut state: State,
+    first_play_urgency: Option<Num>,
+    value: impl Fn(&LazyMctsNode<State, R, A>, &State::Player, Nat) -> Num,
+) -> (NodeId, State)
+    where
+        State: GameTrait,
+        R: Clone,
+        A: Clone + Default,
+{
+    let mut current_node_id = start;
+    while tree.get(current_node_id).unwrap().has_children() {
+        let node = tree.get(current_node_id).unwrap();
+        let turn = state.player_turn();
+        let parent_visits = node.value().n_visits;
+        let has_urgent_child = first_play_urgency.is_some_and(|fpu| {
+            node.children().any(|child| value(child.value(), &turn, parent_visits) > fpu)
+        });
+        if node.value().can_add_child() && !has_urgent_child {
+            break;
+        }
+        current_node_id = best_child_by::<State, _, _>(tree, current_node_id, |child, visits| {
+            value(child, &turn, visits)
+        });
+        let m = tree
+            .get(current_node_id)
+            .unwrap()
+            .value()
+            .incoming_move()
+            .expect("A child always has an incoming move");
+        state.do_move(m);
+    }
+    (current_node_id, state)
+}
+
+/// Goes down from `start` choosing at each node the child given by `best_child` until a leaf,
+/// and returns it with its state. `state` must be the state of `start`.
+pub(crate) fn select_leaf_by<State, R, A>(
+    tree: &LazyMctsTree<State, R, A>,
+    start: NodeId,
+    mut state: State,
+    best_child: impl Fn(&LazyMctsTree<State, R, A>, &State::Player, NodeId) -> NodeId,
+) -> (NodeId, State)
+    where
+        State: GameTrait,
+        R: Clone,
+        A: Clone + Default,
+{
+    let mut current_node_id = start;
+    while tree.get(current_node_id).unwrap().has_children() {
+        current_node_id = best_child(tree, &state.player_turn(), current_node_id);
+        let m = tree
+            .get(current_node_id)
+            .unwrap()
+            .value()
+            .incoming_move()
+            .expect("A child always has an incoming move");
+        state.do_move(m);
+    }
+    (current_node_id, state)
+}
+
+/// The child of `parent_id` with the greatest `value`, given the visits of the parent.
+pub(crate) fn best_child_by<State, R, A>(
+    tree: &LazyMctsTree<State, R, A>,
+    parent_id: NodeId,
+    value: impl Fn(&LazyMctsNode<State, R, A>, Nat) -> Num,
+) -> NodeId
+    where
+        State: GameTrait,
+        R: Clone,
+        A: Clone + Default,
+{
+    let parent_node = tree.get(parent_id).unwrap();
+    let n_visits = parent_node.value().n_visits;
+    parent_node
+        .children()
+        .max_by_key(|child| value(child.value(), n_visits))
+        .unwrap()
+        .id()
+}
+
+/// Explores at least once each child node, before going deeper, unless the evaluator has a first
+/// play urgency.
 pub struct DefaultLazyTreePolicy<State: GameTrait, EV: Evaluator<State, Reward, A>, A: Clone +
 Default, Reward: Clone> {
     phantom_state: PhantomData<State>,
@@ -66,79 +184,119 @@ Default, Reward: Clone> {
     phamtom_r: PhantomData<Reward>,
 }
 
-impl<State: GameTrait, EV: Evaluator<State, Reward, A, Args=f64>, A: Clone + Default,
+impl<State: GameTrait, EV: Evaluator<State, Reward, A>, A: Clone + Default,
     Reward: Clone>
 DefaultLazyTreePolicy<State, EV, A, Reward>
     where
-        Reward: Div + ToPrimitive + Add + Zero,
+        Reward: Zero,
 {
+    /// Goes down the tree from `start` choosing at each node the best child for the player to
+    /// move, and returns the first node which can still be expanded with its state. `state` must
+    /// be the state of `start`.
     pub fn select(
         tree: &mut LazyMctsTree<State, Reward, A>,
-        turn: &State::Player,
+        start: NodeId,
+        state: State,
         evaluator_args: &EV::Args,
-    ) -> NodeId {
-        let mut current_node_id = tree.root().id();
-        while tree.get(current_node_id).unwrap().has_children() {
-            if tree.get(current_node_id).unwrap().value().can_add_child() {
-                return current_node_id;
-            } else {
-                current_node_id =
-                    Self::best_child(tree, turn, current_node_id, evaluator_args);
-            }
-        }
-        current_node_id
+    ) -> (NodeId, State) {
+        let first_play_urgency = EV::first_play_urgency(evaluator_args);
+        select_by(tree, start, state, first_play_urgency, |child, turn, visits| {
+            EV::eval_child(child, turn, visits, evaluator_args)
+        })
     }
 
+    /// Expands the node, `state` must be the state of the node.
     pub fn expand(
-        mut node_to_expand: NodeMut<LazyMctsNode<State, Reward, A>>,
-        root_state: State,
+        node_to_expand: NodeMut<LazyMctsNode<State, Reward, A>>,
+        new_state: State,
     ) -> (NodeId, State) {
-        let mut new_state = Self::update_state(root_state, &node_to_expand.value().state);
-        if !node_to_expand.value().can_add_child() {
-            return (node_to_expand.id(), new_state);
-        }
-        let unvisited_moves = &mut node_to_expand.value().unvisited_moves;
-        let index = thread_rng().gen_range(0..unvisited_moves.len());
-        let move_to_expand = unvisited_moves[index].clone();
-        unvisited_moves[index] = unvisited_moves.last().unwrap().clone();
-        unvisited_moves.pop();
-
-        let mut new_historic = node_to_expand.value().state.clone();
-        new_state.do_move(&move_to_expand);
-        new_historic.push(move_to_expand);
-
-        let new_node = MctsNode {
-            sum_rewards: num_traits::zero(),
-            n_visits: 0,
-            unvisited_moves: new_state.legals_moves(),
-            hash: new_state.hash(),
-            state: new_historic,
-            additional_info: Default::default(),
-        };
-
-        (node_to_expand.append(new_node).id(), new_state)
+        expand(node_to_expand, new_state)
+    }
+}
+
+/// The moves to expand from a node of `state`: none for a final state, even if the game still
+/// gives legal moves.
+pub(crate) fn expandable_moves<State: GameTrait>(state: &State) -> Vec<State::Move> {
+    if state.is_final() {
+        vec![]
+    } else {
+        state.legals_moves()
+    }
+}
+
+/// The root of a lazy tree.
+pub(crate) fn root_node<State, R, A>(root_state: &State) -> LazyMctsNode<State, R, A>
+    where
+        State: GameTrait,
+        R: Clone + Zero,
+        A: Clone + Default,
+{
+    MctsNode {
+        sum_rewards: Zero::zero(),
+        sum_squared_rewards: Zero::zero(),
+        n_visits: 0,
+        unvisited_moves: expandable_moves(root_state),
+        hash: root_state.hash(),
+        state: None,
+        additional_info: Default::default(),
     }
 }
 
+/// Expands a random unvisited move of a node of a lazy tree, `state` must be the state of the
+/// node. Returns the new child with its state, or the node if it can't be expanded.
+pub(crate) fn expand<State, R, A>(
+    mut node_to_expand: NodeMut<LazyMctsNode<State, R, A>>,
+    mut new_state: State,
+) -> (NodeId, State)
+    where
+        State: GameTrait,
+        R: Clone + Zero,
+        A: Clone + Default,
+{
+    if !node_to_expand.value().can_add_child() {
+        return (node_to_expand.id(), new_state);
+    }
+    let unvisited_moves = &mut node_to_expand.value().unvisited_moves;
+    let index = search_rng().gen_range(0..unvisited_moves.len());
+    let move_to_expand = unvisited_moves[index].clone();
+    unvisited_moves[index] = unvisited_moves.last().unwrap().clone();
+    unvisited_moves.pop();
+
+    new_state.do_move(&move_to_expand);
+
+    let new_node = MctsNode {
+        sum_rewards: num_traits::zero(),
+        sum_squared_rewards: num_traits::zero(),
+        n_visits: 0,
+        unvisited_moves: expandable_moves(&new_state),
+        hash: new_state.hash(),
+        state: Some(move_to_expand),
+        additional_info: Default::default(),
+    };
+
+    (node_to_expand.append(new_node).id(), new_state)
+}
+
 impl<State, EV, A, Reward>
 LazyTreePolicy<State, EV, A, Reward> for DefaultLazyTreePolicy<State, EV, A, Reward>
     where
         State: GameTrait,
-        Reward: Clone + Div + Add + ToPrimitive + Zero,
-        EV: Evaluator<State, Reward, A, Args=f64>,
+        Reward: Clone + Zero,
+        EV: Evaluator<State, Reward, A>,
         A: Clone + Default
 {
-    fn tree_policy(
+    fn tree_policy_from(
         tree: &mut LazyMctsTree<State, Reward, A>,
+        start: NodeId,
         root_state: State,
         evaluator_args: &EV::Args,
     ) -> (NodeId, State) {
-        let master_player = root_state.player_turn();
-        let selected_node_id = Self::select(tree, &master_player, evaluator_args);
+        let state = Self::update_state(root_state, &move_path(tree, start));
+        let (selected_node_id, state) = Self::select(tree, start, state, evaluator_args);
         let node = tree
             .get_mut(selected_node_id)
             .unwrap();
-        Self::expand(node, root_state)
+        Self::expand(node, state)
     }
 
     fn update_state(mut root_state: State, historic: &[State::Move]) -> State {
@@ -154,46 +312,38 @@ LazyTreePolicy<State, EV, A, Reward> for DefaultLazyTreePolicy<State, EV, A, Rew
         parent_id: NodeId,
         eval_args: &EV::Args,
     ) -> NodeId {
-        let parent_node = tree.get(parent_id).unwrap();
-        let n_visits = parent_node.value().n_visits;
-        parent_node
-            .children()
-            .max_by_key(|child| EV::eval_child(child.value(), turn, n_visits, eval_args))
-            .unwrap()
-            .id()
+        best_child_by::<State, _, _>(tree, parent_id, |child, visits| {
+            EV::eval_child(child, turn, visits, eval_args)
+        })
     }
 }
 
-/// Uses UCT to evaluate nodes, and evaluates an end state with 1 if the player won.
+/// Uses UCT to evaluate nodes from the point of view of the player choosing between them, and
+/// evaluates an end state from its outcome: 1 for the winner, 0.5 for a draw (max^n).
 pub struct DefaultUctEvaluator;
 
-impl<State: GameTrait, AdditionalInfo: Clone + Default, Reward: Clone + Div + Zero + ToPrimitive
-+ Add>
-Evaluator<State, Reward, AdditionalInfo>
+impl<State: GameTrait, AdditionalInfo: Clone + Default>
+Evaluator<State, RewardVec<State::Player>, AdditionalInfo>
 for DefaultUctEvaluator
 {
     type Args = f64;
-    type EvalResult = Nat;
+    type EvalResult = RewardVec<State::Player>;
 
-    fn eval_child(
-        child: &LazyMctsNode<State, Reward, AdditionalInfo>,
-        _turn: &State::Player,
+    fn eval_child<T: Clone>(
+        child: &MctsNode<T, State::Move, RewardVec<State::Player>, AdditionalInfo>,
+        turn: &State::Player,
         parent_visits: Nat,
         &c: &Self::Args,
     ) -> Num {
         uct_value(
             parent_visits,
-            child.sum_rewards.to_f64().unwrap(),
+            child.sum_rewards.get(turn),
             child.n_visits,
             c,
         )
     }
 
-    fn evaluate_leaf(child: State, turn: &State::Player) -> Self::EvalResult {
-        if child.get_winner() == *turn {
-            1
-        } else {
-            0
-        }
+    fn evaluate_leaf(child: &State, _turn: &State::Player) -> Self::EvalResult {
+        RewardVec::from_outcome(&child.outcome(), child.score_bounds())
     }
 }
@@ -0,0 +1,524 @@
+use std::borrow::Cow;
+use std::marker::PhantomData;
+
+use ego_tree::NodeId;
+use num_traits::Zero;
+
+use crate::aliases::{LazyMctsNode, LazyMctsTree, Nat, Num, PlayedMoves};
+use crate::defaults::{
+    best_child_by, expand, select_by, select_leaf_by, DefaultBackProp, DefaultPlayout,
+    DefaultUctEvaluator,
+};
+use crate::mcts_node::move_path;
+use crate::memory::{MemoryLimit, MemoryUsage};
+use crate::reward::{PlayerReward, RewardVec};
+use crate::search_core::{SearchCore, SearchPolicies};
+use crate::stats::SearchStats;
+use crate::stopping::{CancellationToken, SearchReport, StoppingRule};
+use crate::traits::{BackPropPolicy, Evaluator, GameTrait, Playout, SearchObserver};
+use crate::tree_search::FinalMove;
+
+/// Object safe counterpart of `Evaluator`, its arguments are stored in the evaluator. The
+/// evaluation of a final state is the reward backpropagated.
+pub trait DynEvaluator<State: GameTrait, Reward: Clone, A: Clone + Default> {
+    fn eval_child(
+        &self,
+        child: &LazyMctsNode<State, Reward, A>,
+        turn: &State::Player,
+        parent_visits: Nat,
+    ) -> Num;
+
+    fn evaluate_leaf(&self, state: &State, turn: &State::Player) -> Reward;
+
+    /// See `Evaluator::first_play_urgency`.
+    fn first_play_urgency(&self) -> Option<Num> {
+        None
+    }
+}
+
+/// Object safe counterpart of `LazyTreePolicy`.
+pub trait DynTreePolicy<State: GameTrait, Reward: Clone, A: Clone + Default> {
+    /// Selects a node from `start` and expands it, returns the node with its state. `root_state`
+    /// is the state of the root.
+    fn tree_policy_from(
+        &self,
+        tree: &mut LazyMctsTree<State, Reward, A>,
+        start: NodeId,
+        root_state: State,
+        evaluator: &dyn DynEvaluator<State, Reward, A>,
+    ) -> (NodeId, State);
+
+    /// See `LazyTreePolicy::select_leaf`.
+    fn select_leaf(
+        &self,
+        tree: &LazyMctsTree<State, Reward, A>,
+        start: NodeId,
+        root_state: State,
+        evaluator: &dyn DynEvaluator<State, Reward, A>,
+    ) -> (NodeId, State) {
+        let state = node_state(tree, start, root_state);
+        select_leaf_by(tree, start, state, |tree, turn, parent_id| {
+            self.best_child(tree, turn, parent_id, evaluator)
+        })
+    }
+
+    fn best_child(
+        &self,
+        tree: &LazyMctsTree<State, Reward, A>,
+        turn: &State::Player,
+        parent_id: NodeId,
+        evaluator: &dyn DynEvaluator<State, Reward, A>,
+    ) -> NodeId;
+}
+
+/// Object safe counterpart of `Playout`, its arguments are stored in the policy.
+pub trait DynPlayout<State: GameTrait, Reward> {
+    /// See `Playout::playout_from`.
+    fn playout(&mut self, historic: &[State::Move], state: State) -> (State, PlayedMoves<State>);
+
+    /// See `Playout::backprop`.
+    fn backprop(
+        &mut self,
+        _historic: &[State::Move],
+        _moves: &[(State::Player, State::Move)],
+        _eval: &Reward,
+    ) {
+    }
+}
+
+/// Object safe counterpart of `BackPropPolicy`.
+pub trait DynBackProp<State: GameTrait, Reward: Clone, A: Clone + Default> {
+    fn backprop(&self, tree: &mut LazyMctsTree<State, Reward, A>, leaf: NodeId, reward: Reward);
+}
+
+/// Uses a static `Evaluator` with its arguments as a `DynEvaluator`.
+pub struct StaticEvaluator<EV, Args> {
+    pub args: Args,
+    phantom_ev: PhantomData<EV>,
+}
+
+impl<EV, Args> StaticEvaluator<EV, Args> {
+    pub fn new(args: Args) -> Self {
+        Self { args, phantom_ev: PhantomData }
+    }
+}
+
+impl<State, EV, Reward, A> DynEvaluator<State, Reward, A> for StaticEvaluator<EV, EV::Args>
+    where
+        State: GameTrait,
+        EV: Evaluator<State, Reward, A, EvalResult = Reward>,
+        Reward: Clone,
+        A: Clone + Default,
+{
+    fn eval_child(
+        &self,
+        child: &LazyMctsNode<State, Reward, A>,
+        turn: &State::Player,
+        parent_visits: Nat,
+    ) -> Num {
+        EV::eval_child(child, turn, parent_visits, &self.args)
+    }
+
+    fn evaluate_leaf(&self, state: &State, turn: &State::Player) -> Reward {
+        EV::evaluate_leaf(state, turn)
+    }
+
+    fn first_play_urgency(&self) -> Option<Num> {
+        EV::first_play_urgency(&self.args)
+    }
+}
+
+/// Uses a static `Playout` with its arguments as a `DynPlayout`.
+pub struct StaticPlayout<PP, Args> {
+    pub policy: PP,
+    pub args: Args,
+}
+
+impl<State, PP, Reward> DynPlayout<State, Reward> for StaticPlayout<PP, PP::Args>
+    where
+        State: GameTrait,
+        PP: Playout<State, Reward>,
+        PP::Args: Clone,
+{
+    fn playout(&mut self, historic: &[State::Move], state: State) -> (State, PlayedMoves<State>) {
+        self.policy.playout_from(historic, state, self.args.clone())
+    }
+
+    fn backprop(
+        &mut self,
+        historic: &[State::Move],
+        moves: &[(State::Player, State::Move)],
+        eval: &Reward,
+    ) {
+        self.policy.backprop(historic, moves, eval)
+    }
+}
+
+/// Uses a static `BackPropPolicy` as a `DynBackProp`.
+pub struct StaticBackProp<BP> {
+    phantom_bp: PhantomData<BP>,
+}
+
+impl<BP> Default for StaticBackProp<BP> {
+    fn default() -> Self {
+        Self { phantom_bp: PhantomData }
+    }
+}
+
+impl<State, BP, Reward, A> DynBackProp<State, Reward, A> for StaticBackProp<BP>
+    where
+        State: GameTrait,
+        BP: BackPropPolicy<Option<State::Move>, State::Move, Reward, A>,
+        Reward: Clone,
+        A: Clone + Default,
+{
+    fn backprop(&self, tree: &mut LazyMctsTree<State, Reward, A>, leaf: NodeId, reward: Reward) {
+        BP::backprop(tree, leaf, reward)
+    }
+}
+
+/// Same as the `DefaultLazyTreePolicy`, with any `DynEvaluator`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DefaultDynTreePolicy;
+
+impl<State, Reward, A> DynTreePolicy<State, Reward, A> for DefaultDynTreePolicy
+    where
+        State: GameTrait,
+        Reward: Clone + Zero,
+        A: Clone + Default,
+{
+    fn tree_policy_from(
+        &self,
+        tree: &mut LazyMctsTree<State, Reward, A>,
+        start: NodeId,
+        root_state: State,
+        evaluator: &dyn DynEvaluator<State, Reward, A>,
+    ) -> (NodeId, State) {
+        let state = node_state(tree, start, root_state);
+        let first_play_urgency = evaluator.first_play_urgency();
+        let (node_id, state) =
+            select_by(tree, start, state, first_play_urgency, |child, turn, visits| {
+                evaluator.eval_child(child, turn, visits)
+            });
+        expand(tree.get_mut(node_id).unwrap(), state)
+    }
+
+    fn best_child(
+        &self,
+        tree: &LazyMctsTree<State, Reward, A>,
+        turn: &State::Player,
+        parent_id: NodeId,
+        evaluator: &dyn DynEvaluator<State, Reward, A>,
+    ) -> NodeId {
+        best_child_by::<State, _, _>(tree, parent_id, |child, visits| {
+            evaluator.eval_child(child, turn, visits)
+        })
+    }
+}
+
+/// The state of `node_id`, replaying its moves on `root_state`.
+fn node_state<State, R, A>(
+    tree: &LazyMctsTree<State, R, A>,
+    node_id: NodeId,
+    mut root_state: State,
+) -> State
+    where
+        State: GameTrait,
+        R: Clone,
+        A: Clone + Default,
+{
+    for m in move_path(tree, node_id) {
+        root_state.do_move(&m);
+    }
+    root_state
+}
+
+/// Same as the `LazyMcts` but its policies are trait objects, so they can be chosen at runtime,
+/// for example by name from a configuration. The arguments of the policies are stored in them.
+pub struct DynMcts<'a, State, Reward, AddInfo>
+    where
+        State: GameTrait,
+        Reward: Clone,
+        AddInfo: Clone + Default,
+{
+    core: SearchCore<'a, State, Reward, AddInfo>,
+    tree_policy: Box<dyn DynTreePolicy<State, Reward, AddInfo>>,
+    playout_policy: Box<dyn DynPlayout<State, Reward>>,
+    backprop_policy: Box<dyn DynBackProp<State, Reward, AddInfo>>,
+    evaluator: Box<dyn DynEvaluator<State, Reward, AddInfo>>,
+}
+
+/// The boxed policies of a `DynMcts`, borrowed apart from its `SearchCore`.
+struct DynPolicies<'p, State: GameTrait, R, A> {
+    tree_policy: &'p dyn DynTreePolicy<State, R, A>,
+    playout_policy: &'p mut dyn DynPlayout<State, R>,
+    backprop_policy: &'p dyn DynBackProp<State, R, A>,
+    evaluator: &'p dyn DynEvaluator<State, R, A>,
+}
+
+impl<State, R, A> SearchPolicies<State, R, A> for DynPolicies<'_, State, R, A>
+    where
+        State: GameTrait,
+        R: Clone,
+        A: Clone + Default,
+{
+    type EvalResult = R;
+
+    fn tree_policy_from(
+        &mut self,
+        tree: &mut LazyMctsTree<State, R, A>,
+        start: NodeId,
+        root_state: State,
+    ) -> (NodeId, State) {
+        self.tree_policy.tree_policy_from(tree, start, root_state, self.evaluator)
+    }
+
+    fn select_leaf(
+        &mut self,
+        tree: &LazyMctsTree<State, R, A>,
+        start: NodeId,
+        root_state: State,
+    ) -> (NodeId, State) {
+        self.tree_policy.select_leaf(tree, start, root_state, self.evaluator)
+    }
+
+    fn playout(&mut self, historic: &[State::Move], state: State) -> (State, PlayedMoves<State>) {
+        self.playout_policy.playout(historic, state)
+    }
+
+    fn evaluate_leaf(&self, state: &State, turn: &State::Player) -> R {
+        self.evaluator.evaluate_leaf(state, turn)
+    }
+
+    fn learn(
+        &mut self,
+        historic: &[State::Move],
+        moves: &[(State::Player, State::Move)],
+        eval: &R,
+    ) {
+        self.playout_policy.backprop(historic, moves, eval)
+    }
+
+    fn backprop(&mut self, tree: &mut LazyMctsTree<State, R, A>, leaf: NodeId, eval: R) {
+        self.backprop_policy.backprop(tree, leaf, eval)
+    }
+
+    fn best_child(
+        &self,
+        tree: &LazyMctsTree<State, R, A>,
+        turn: &State::Player,
+        parent_id: NodeId,
+    ) -> NodeId {
+        self.tree_policy.best_child(tree, turn, parent_id, self.evaluator)
+    }
+}
+
+impl<'a, State: GameTrait> DynMcts<'a, State, RewardVec<State::Player>, ()> {
+    /// The policies of the `DefaultMcts`, with `c` as exploration constant.
+    pub fn uct(root_state: &'a State, c: f64) -> Self {
+        Self::new(
+            root_state,
+            Box::new(DefaultDynTreePolicy),
+            Box::new(StaticPlayout { policy: DefaultPlayout, args: () }),
+            Box::<StaticBackProp<DefaultBackProp>>::default(),
+            Box::new(StaticEvaluator::<DefaultUctEvaluator, _>::new(c)),
+        )
+    }
+}
+
+impl<'a, State, R, A> DynMcts<'a, State, R, A>
+    where
+        State: GameTrait,
+        R: Clone + Zero,
+        A: Clone + Default,
+{
+    pub fn new(
+        root_state: &'a State,
+        tree_policy: Box<dyn DynTreePolicy<State, R, A>>,
+        playout_policy: Box<dyn DynPlayout<State, R>>,
+        backprop_policy: Box<dyn DynBackProp<State, R, A>>,
+        evaluator: Box<dyn DynEvaluator<State, R, A>>,
+    ) -> Self {
+        Self {
+            core: SearchCore::new(Cow::Borrowed(root_state), 0),
+            tree_policy,
+            playout_policy,
+            backprop_policy,
+            evaluator,
+        }
+    }
+
+    /// The search state with the policies, borrowed apart.
+    fn split(&mut self) -> (&mut SearchCore<'a, State, R, A>, DynPolicies<'_, State, R, A>) {
+        let policies = DynPolicies {
+            tree_policy: &*self.tree_policy,
+            playout_policy: &mut *self.playout_policy,
+            backprop_policy: &*self.backprop_policy,
+            evaluator: &*self.evaluator,
+        };
+        (&mut self.core, policies)
+    }
+
+    /// Executes one selection, expansion?, simulation, backpropagation.
+    pub fn execute(&mut self) {
+        self.execute_observed(&mut ());
+    }
+
+    /// Same as `execute` but the phases of the iteration are observed by `observer`.
+    pub fn execute_observed<O: SearchObserver<State, R>>(&mut self, observer: &mut O) {
+        let (core, mut policies) = self.split();
+        core.execute_observed(&mut policies, observer);
+    }
+
+    /// See `LazyMcts::search`.
+    pub fn search(&mut self, budget: usize, stopping_rule: &StoppingRule) -> SearchReport
+        where
+            R: PlayerReward<State::Player>,
+    {
+        self.search_observed(budget, stopping_rule, &mut ())
+    }
+
+    /// See `LazyMcts::search_observed`.
+    pub fn search_observed<O: SearchObserver<State, R>>(
+        &mut self,
+        budget: usize,
+        stopping_rule: &StoppingRule,
+        observer: &mut O,
+    ) -> SearchReport
+        where
+            R: PlayerReward<State::Player>,
+    {
+        let (core, mut policies) = self.split();
+        core.search(&mut policies, budget, stopping_rule, observer)
+    }
+
+    /// Returns the best move from the root for the tree policy.
+    pub fn best_move(&self) -> State::Move {
+        self.core.best_move(|tree, turn, root_id| {
+            self.tree_policy.best_child(tree, turn, root_id, &*self.evaluator)
+        })
+    }
+
+    /// See `LazyMcts::final_move`.
+    pub fn final_move(&self, criterion: FinalMove) -> State::Move
+        where
+            R: PlayerReward<State::Player>,
+    {
+        self.core.final_move(criterion, |tree, turn, root_id| {
+            self.tree_policy.best_child(tree, turn, root_id, &*self.evaluator)
+        })
+    }
+
+    /// The statistics of all the iterations executed.
+    pub fn stats(&self) -> SearchStats {
+        self.core.stats()
+    }
+
+    /// See `LazyMcts::seed`.
+    pub fn seed(&mut self, seed: u64) {
+        self.core.seed(seed);
+    }
+
+    /// See `LazyMcts::set_timing`.
+    pub fn set_timing(&mut self, timing: bool) {
+        self.core.timing = timing;
+    }
+
+    /// Bounds the memory used by the tree, see `MemoryLimit`.
+    pub fn set_memory_limit(&mut self, memory_limit: Option<MemoryLimit>) {
+        self.core.set_memory_limit(memory_limit);
+    }
+
+    pub fn memory_usage(&self) -> MemoryUsage {
+        self.core.memory_usage
+    }
+
+    /// Lets `search` be interrupted from another thread by cancelling `token`.
+    pub fn set_cancellation_token(&mut self, token: Option<CancellationToken>) {
+        self.core.cancellation_token = token;
+    }
+
+    /// See `LazyMcts::advance`.
+    pub fn advance(&mut self, m: &State::Move) -> bool
+        where
+            State::Move: PartialEq,
+    {
+        self.core.advance(m)
+    }
+
+    /// Changes the playout policy, the tree is kept.
+    pub fn set_playout_policy(&mut self, playout_policy: Box<dyn DynPlayout<State, R>>) {
+        self.playout_policy = playout_policy;
+    }
+
+    /// Changes the evaluator, the tree is kept.
+    pub fn set_evaluator(&mut self, evaluator: Box<dyn DynEvaluator<State, R, A>>) {
+        self.evaluator = evaluator;
+    }
+
+    pub fn root_state(&self) -> &State {
+        &self.core.root_state
+    }
+
+    /// See `LazyMcts::tree`.
+    pub fn tree(&self) -> &LazyMctsTree<State, R, A> {
+        &self.core.tree
+    }
+}
+
+#[test]
+fn test_policies_chosen_by_name() {
+    use crate::test_games::Guess;
+    use crate::evaluators::Ucb1TunedEvaluator;
+
+    type Reward = RewardVec<u8>;
+
+    let evaluator = |name: &str| -> Box<dyn DynEvaluator<Guess, Reward, ()>> {
+        match name {
+            "uct" => Box::new(StaticEvaluator::<DefaultUctEvaluator, _>::new(1.)),
+            _ => Box::new(StaticEvaluator::<Ucb1TunedEvaluator, _>::new(())),
+        }
+    };
+    let game = Guess(None);
+    for name in ["uct", "ucb1-tuned"] {
+        let mut mcts = DynMcts::new(
+            &game,
+            Box::new(DefaultDynTreePolicy),
+            Box::new(StaticPlayout { policy: DefaultPlayout, args: () }),
+            Box::<StaticBackProp<DefaultBackProp>>::default(),
+            evaluator(name),
+        );
+        for _ in 0..200 {
+            mcts.execute();
+        }
+        assert_eq!(mcts.best_move(), 5, "{}", name);
+    }
+}
+
+#[test]
+fn test_dyn_search_shares_the_features_of_the_lazy_search() {
+    use crate::test_games::{Choice, Race};
+    use crate::MemoryBudget::Nodes;
+    use crate::MemoryPolicy;
+
+    let game = Race(0);
+    let mut mcts = DynMcts::uct(&game, 1.);
+    let policy = MemoryPolicy::RecycleLeastVisited;
+    mcts.set_memory_limit(Some(MemoryLimit { budget: Nodes(50), policy }));
+    let report = mcts.search(500, &StoppingRule::Never);
+    assert_eq!(report.iterations, 500);
+    assert!(report.nodes_pruned > 0);
+    assert!(mcts.memory_usage().nodes <= 50);
+    assert_eq!(mcts.stats().iterations, 500);
+
+    let token = CancellationToken::new();
+    token.cancel();
+    mcts.set_cancellation_token(Some(token));
+    assert!(mcts.search(100, &StoppingRule::Never).cancelled);
+
+    let game = Choice(None);
+    let mut mcts = DynMcts::uct(&game, 1.);
+    mcts.search(100, &StoppingRule::Never);
+    assert_eq!(mcts.final_move(FinalMove::MostVisited), 1);
+    assert_eq!(mcts.final_move(FinalMove::MaxMeanReward), 1);
+}
@@ -0,0 +1,311 @@
+use std::marker::PhantomData;
+
+use ego_tree::NodeId;
+use num_traits::Zero;
+use rand::Rng;
+
+use crate::aliases::{EagerMctsNode, EagerMctsTree};
+use crate::defaults::{expandable_moves, has_urgent_child};
+use crate::mcts_node::MctsNode;
+use crate::rng::search_rng;
+use crate::traits::{BackPropPolicy, EagerTreePolicy, Evaluator, GameTrait, Playout};
+
+/// What a node of an eager tree stores about its state.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serialize", serde(bound(
+    serialize = "State: serde::Serialize, State::Move: serde::Serialize, \
+                 State::Player: serde::Serialize",
+    deserialize = "State: serde::Deserialize<'de>, State::Move: serde::Deserialize<'de>, \
+                   State::Player: serde::Deserialize<'de>",
+)))]
+pub struct CachedState<State: GameTrait> {
+    /// The move leading to the node, `None` for the root.
+    pub incoming_move: Option<State::Move>,
+    /// The number of moves from the root.
+    pub depth: usize,
+    /// The player to move, so the selection doesn't need the states.
+    pub player: State::Player,
+    /// The state of the node if it's cached, the root always caches its state.
+    pub state: Option<State>,
+}
+
+/// The state of a node, cloned from the node or replayed from its closest ancestor caching its
+/// state.
+pub fn state_of<State, R, A>(tree: &EagerMctsTree<State, R, A>, node_id: NodeId) -> State
+    where
+        State: GameTrait,
+        R: Clone,
+        A: Clone + Default,
+{
+    let mut moves = vec![];
+    let mut node = tree.get(node_id).unwrap();
+    loop {
+        let cached = &node.value().state;
+        if let Some(state) = &cached.state {
+            let mut state = state.clone();
+            for m in moves.iter().rev() {
+                state.do_move(m);
+            }
+            return state;
+        }
+        moves.push(cached.incoming_move.clone().expect("The root always caches its state"));
+        node = node.parent().unwrap();
+    }
+}
+
+/// Same as the `DefaultLazyTreePolicy`, explores at least once each child node before going
+/// deeper, but reads the states from the tree.
+pub struct DefaultEagerTreePolicy<State: GameTrait, EV: Evaluator<State, Reward, A>, A: Clone +
+Default, Reward: Clone> {
+    phantom_state: PhantomData<State>,
+    phantom_a: PhantomData<A>,
+    phantom_ev: PhantomData<EV>,
+    phamtom_r: PhantomData<Reward>,
+}
+
+impl<State, EV, A, Reward> DefaultEagerTreePolicy<State, EV, A, Reward>
+    where
+        State: GameTrait,
+        EV: Evaluator<State, Reward, A>,
+        A: Clone + Default,
+        Reward: Clone + Zero,
+{
+    /// Goes down the tree choosing at each node the best child for the player to move, and
+    /// returns the first node which can still be expanded.
+    pub fn select(tree: &EagerMctsTree<State, Reward, A>, evaluator_args: &EV::Args) -> NodeId {
+        let mut current_node_id = tree.root().id();
+        loop {
+            let node = tree.get(current_node_id).unwrap();
+            let turn = &node.value().state.player;
+            if !node.has_children()
+                || node.value().can_add_child()
+                    && !has_urgent_child::<State, EV, _, _, _>(node, turn, evaluator_args) {
+                return current_node_id;
+            }
+            current_node_id = Self::best_child(tree, turn, current_node_id, evaluator_args);
+        }
+    }
+
+    /// Expands the node, `state` must be the state of the node.
+    pub fn expand(
+        tree: &mut EagerMctsTree<State, Reward, A>,
+        node_id: NodeId,
+        mut new_state: State,
+        cache_interval: usize,
+    ) -> (NodeId, State) {
+        let mut node_to_expand = tree.get_mut(node_id).unwrap();
+        if !node_to_expand.value().can_add_child() {
+            return (node_id, new_state);
+        }
+        let unvisited_moves = &mut node_to_expand.value().unvisited_moves;
+        let index = search_rng().gen_range(0..unvisited_moves.len());
+        let move_to_expand = unvisited_moves.swap_remove(index);
+        new_state.do_move(&move_to_expand);
+
+        let depth = node_to_expand.value().state.depth + 1;
+        // With an interval of 0 only the root caches its state.
+        let cached = cache_interval != 0 && depth % cache_interval == 0;
+        let unvisited_moves = expandable_moves(&new_state);
+        let new_node = MctsNode {
+            sum_rewards: num_traits::zero(),
+            sum_squared_rewards: num_traits::zero(),
+            n_visits: 0,
+            unvisited_moves,
+            hash: new_state.hash(),
+            state: CachedState {
+                incoming_move: Some(move_to_expand),
+                depth,
+                player: new_state.player_turn(),
+                state: if cached { Some(new_state.clone()) } else { None },
+            },
+            additional_info: Default::default(),
+        };
+
+        (node_to_expand.append(new_node).id(), new_state)
+    }
+}
+
+impl<State, EV, A, Reward> EagerTreePolicy<State, EV, A, Reward>
+for DefaultEagerTreePolicy<State, EV, A, Reward>
+    where
+        State: GameTrait,
+        EV: Evaluator<State, Reward, A>,
+        A: Clone + Default,
+        Reward: Clone + Zero,
+{
+    fn tree_policy(
+        tree: &mut EagerMctsTree<State, Reward, A>,
+        cache_interval: usize,
+        evaluator_args: &EV::Args,
+    ) -> (NodeId, State) {
+        let selected_node_id = Self::select(tree, evaluator_args);
+        let state = state_of(tree, selected_node_id);
+        Self::expand(tree, selected_node_id, state, cache_interval)
+    }
+
+    fn best_child(
+        tree: &EagerMctsTree<State, Reward, A>,
+        turn: &State::Player,
+        parent_id: NodeId,
+        eval_args: &EV::Args,
+    ) -> NodeId {
+        let parent_node = tree.get(parent_id).unwrap();
+        let n_visits = parent_node.value().n_visits;
+        parent_node
+            .children()
+            .max_by_key(|child| EV::eval_child(child.value(), turn, n_visits, eval_args))
+            .unwrap()
+            .id()
+    }
+}
+
+/// A sibling of the `LazyMcts` storing the states in the nodes, it's faster when the states are
+/// cheap to clone. With a cache interval of k only the states of the nodes at a depth multiple of
+/// k are stored and the other states are replayed from them, 0 stores only the root state. It
+/// shares the evaluators, playouts and backprop policies of the `LazyMcts`.
+#[derive(Clone)]
+pub struct EagerMcts<State, TP, PP, BP, EV, AddInfo, Reward>
+    where
+        State: GameTrait,
+        TP: EagerTreePolicy<State, EV, AddInfo, Reward>,
+        PP: Playout<State, EV::EvalResult>,
+        BP: BackPropPolicy<CachedState<State>, State::Move, Reward, AddInfo, EV::EvalResult>,
+        EV: Evaluator<State, Reward, AddInfo>,
+        AddInfo: Clone + Default,
+        Reward: Clone,
+{
+    tree_policy: PhantomData<TP>,
+    playout_policy: PP,
+    backprop_policy: PhantomData<BP>,
+    evaluator: PhantomData<EV>,
+    tree: EagerMctsTree<State, Reward, AddInfo>,
+    cache_interval: usize,
+}
+
+impl<State, TP, PP, BP, EV, A, R> EagerMcts<State, TP, PP, BP, EV, A, R>
+    where
+        State: GameTrait,
+        TP: EagerTreePolicy<State, EV, A, R>,
+        PP: Playout<State, EV::EvalResult>,
+        BP: BackPropPolicy<CachedState<State>, State::Move, R, A, EV::EvalResult>,
+        EV: Evaluator<State, R, A>,
+        A: Clone + Default,
+        R: Clone + Zero,
+{
+    /// Creates the search caching every state.
+    pub fn new(root_state: State) -> Self
+        where
+            PP: Default,
+    {
+        Self::with_cache_interval(root_state, 1)
+    }
+
+    pub fn with_cache_interval(root_state: State, cache_interval: usize) -> Self
+        where
+            PP: Default,
+    {
+        Self::with_playout_policy(root_state, cache_interval, PP::default())
+    }
+
+    /// Creates the search with a playout policy, useful when the policy has a state or parameters.
+    pub fn with_playout_policy(
+        root_state: State,
+        cache_interval: usize,
+        playout_policy: PP,
+    ) -> Self {
+        let tree = EagerMctsTree::new(EagerMctsNode::<State, R, A> {
+            sum_rewards: Zero::zero(),
+            sum_squared_rewards: Zero::zero(),
+            n_visits: 0,
+            unvisited_moves: expandable_moves(&root_state),
+            hash: root_state.hash(),
+            state: CachedState {
+                incoming_move: None,
+                depth: 0,
+                player: root_state.player_turn(),
+                state: Some(root_state),
+            },
+            additional_info: Default::default(),
+        });
+        Self {
+            tree_policy: PhantomData,
+            playout_policy,
+            backprop_policy: PhantomData,
+            evaluator: PhantomData,
+            tree,
+            cache_interval,
+        }
+    }
+
+    /// Executes one selection, expansion?, simulation, backpropagation.
+    pub fn execute(&mut self, evaluation_args: &EV::Args, playout_args: PP::Args) {
+        let (node_id, state) =
+            TP::tree_policy(&mut self.tree, self.cache_interval, evaluation_args);
+        let historic = self.move_path(node_id);
+        let (final_state, moves) =
+            self.playout_policy.playout_from(&historic, state, playout_args);
+        let eval = EV::evaluate_leaf(&final_state, &self.tree.root().value().state.player);
+        self.playout_policy.backprop(&historic, &moves, &eval);
+        BP::backprop(&mut self.tree, node_id, eval);
+    }
+
+    /// Returns the best move from the root.
+    pub fn best_move(&self, evaluator_args: &EV::Args) -> State::Move {
+        let root = self.tree.root();
+        let turn = &root.value().state.player;
+        let best_child = TP::best_child(&self.tree, turn, root.id(), evaluator_args);
+        self.tree
+            .get(best_child)
+            .unwrap()
+            .value()
+            .state
+            .incoming_move
+            .clone()
+            .expect("The children of the root always have an incoming move")
+    }
+
+    /// The moves leading from the root to the node.
+    pub fn move_path(&self, node_id: NodeId) -> Vec<State::Move> {
+        let node = self.tree.get(node_id).unwrap();
+        let mut path: Vec<State::Move> = std::iter::once(node)
+            .chain(node.ancestors())
+            .filter_map(|n| n.value().state.incoming_move.clone())
+            .collect();
+        path.reverse();
+        path
+    }
+
+    /// The state of the node.
+    pub fn state_of(&self, node_id: NodeId) -> State {
+        state_of(&self.tree, node_id)
+    }
+
+    pub fn tree(&self) -> &EagerMctsTree<State, R, A> {
+        &self.tree
+    }
+
+    pub fn playout_policy(&self) -> &PP {
+        &self.playout_policy
+    }
+}
+
+#[test]
+fn test_eager_states_match_the_moves() {
+    use crate::test_games::Race;
+    use crate::DefaultEagerMcts;
+
+    for &cache_interval in &[0, 1, 3] {
+        let mut mcts = DefaultEagerMcts::with_cache_interval(Race(0), cache_interval);
+        for _ in 0..200 {
+            mcts.execute(&1., ());
+        }
+        for node in mcts.tree().nodes() {
+            let sum: u8 = mcts.move_path(node.id()).iter().sum();
+            assert_eq!(mcts.state_of(node.id()).0, sum);
+            let cached = &node.value().state;
+            assert_eq!(cached.state.is_some(), cached.depth == 0
+                || cache_interval != 0 && cached.depth % cache_interval == 0);
+        }
+    }
+}
@@ -0,0 +1,266 @@
+use std::marker::PhantomData;
+
+use noisy_float::prelude::n64;
+use rand::Rng;
+use rand_distr::{Beta, Distribution};
+
+use crate::aliases::{Nat, Num};
+use crate::mcts_node::MctsNode;
+use crate::ops::{kl_ucb_value, ucb1_tuned_value, ucb_v_value};
+use crate::reward::RewardVec;
+use crate::rng::search_rng;
+use crate::traits::{Evaluator, GameTrait, HeuristicGame};
+
+/// Evaluates the nodes with `EV`, and the states where the playout was cut off with the heuristic
+/// of the game. The root player is rewarded with its heuristic and the other players with its
+/// complement, as in a two players game.
+pub struct HeuristicEvaluator<EV> {
+    phantom_ev: PhantomData<EV>,
+}
+
+impl<State, EV, Reward, A> Evaluator<State, Reward, A> for HeuristicEvaluator<EV>
+    where
+        State: HeuristicGame,
+        EV: Evaluator<State, Reward, A, EvalResult=RewardVec<State::Player>>,
+        Reward: Clone,
+        A: Clone + Default,
+{
+    type Args = EV::Args;
+    type EvalResult = RewardVec<State::Player>;
+
+    fn eval_child<T: Clone>(
+        child: &MctsNode<T, State::Move, Reward, A>,
+        turn: &State::Player,
+        parent_visits: Nat,
+        args: &Self::Args,
+    ) -> Num {
+        EV::eval_child(child, turn, parent_visits, args)
+    }
+
+    fn evaluate_leaf(child: &State, turn: &State::Player) -> Self::EvalResult {
+        if child.is_final() {
+            return EV::evaluate_leaf(child, turn);
+        }
+        let heuristic = child.heuristic(turn);
+        let mut rewards = RewardVec::uniform(1. - heuristic);
+        rewards.add_reward(turn.clone(), 2. * heuristic - 1.);
+        rewards
+    }
+
+    fn first_play_urgency(args: &Self::Args) -> Option<Num> {
+        EV::first_play_urgency(args)
+    }
+}
+
+/// Arguments of the `ExplorationEvaluator`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ExplorationArgs<Args> {
+    /// The arguments of the wrapped evaluator.
+    pub args: Args,
+    /// The first play urgency, see `Evaluator::first_play_urgency`.
+    pub fpu: Option<f64>,
+    /// The children values get a uniform noise in [0, `noise`], 0 keeps them unchanged.
+    pub noise: f64,
+}
+
+/// Evaluates the nodes and the final states with `EV`, with a first play urgency and a noise on
+/// the values of the children.
+pub struct ExplorationEvaluator<EV> {
+    phantom_ev: PhantomData<EV>,
+}
+
+impl<State, EV, Reward, A> Evaluator<State, Reward, A> for ExplorationEvaluator<EV>
+    where
+        State: GameTrait,
+        EV: Evaluator<State, Reward, A>,
+        Reward: Clone,
+        A: Clone + Default,
+{
+    type Args = ExplorationArgs<EV::Args>;
+    type EvalResult = EV::EvalResult;
+
+    fn eval_child<T: Clone>(
+        child: &MctsNode<T, State::Move, Reward, A>,
+        turn: &State::Player,
+        parent_visits: Nat,
+        args: &Self::Args,
+    ) -> Num {
+        let value = EV::eval_child(child, turn, parent_visits, &args.args);
+        if args.noise > 0. {
+            value + n64(args.noise * search_rng().gen::<f64>())
+        } else {
+            value
+        }
+    }
+
+    fn evaluate_leaf(child: &State, turn: &State::Player) -> Self::EvalResult {
+        EV::evaluate_leaf(child, turn)
+    }
+
+    fn first_play_urgency(args: &Self::Args) -> Option<Num> {
+        args.fpu.map(n64).or_else(|| EV::first_play_urgency(&args.args))
+    }
+}
+
+/// Evaluates the nodes with UCB1-Tuned from the point of view of the player choosing between them,
+/// and the final states from their outcome like the `DefaultUctEvaluator`.
+pub struct Ucb1TunedEvaluator;
+
+impl<State: GameTrait, A: Clone + Default> Evaluator<State, RewardVec<State::Player>, A>
+for Ucb1TunedEvaluator
+{
+    type Args = ();
+    type EvalResult = RewardVec<State::Player>;
+
+    fn eval_child<T: Clone>(
+        child: &MctsNode<T, State::Move, RewardVec<State::Player>, A>,
+        turn: &State::Player,
+        parent_visits: Nat,
+        _args: &Self::Args,
+    ) -> Num {
+        ucb1_tuned_value(
+            parent_visits,
+            child.sum_rewards.get(turn),
+            child.sum_squared_rewards.get(turn),
+            child.n_visits,
+        )
+    }
+
+    fn evaluate_leaf(child: &State, _turn: &State::Player) -> Self::EvalResult {
+        RewardVec::from_outcome(&child.outcome(), child.score_bounds())
+    }
+}
+
+/// Arguments of the `UcbVEvaluator`.
+#[derive(Clone, Copy, Debug)]
+pub struct UcbVArgs {
+    pub c: f64,
+    pub zeta: f64,
+}
+
+impl Default for UcbVArgs {
+    fn default() -> Self {
+        Self { c: 1., zeta: 1.2 }
+    }
+}
+
+/// Evaluates the nodes with UCB-V from the point of view of the player choosing between them, and
+/// the final states from their outcome like the `DefaultUctEvaluator`.
+pub struct UcbVEvaluator;
+
+impl<State: GameTrait, A: Clone + Default> Evaluator<State, RewardVec<State::Player>, A>
+for UcbVEvaluator
+{
+    type Args = UcbVArgs;
+    type EvalResult = RewardVec<State::Player>;
+
+    fn eval_child<T: Clone>(
+        child: &MctsNode<T, State::Move, RewardVec<State::Player>, A>,
+        turn: &State::Player,
+        parent_visits: Nat,
+        args: &Self::Args,
+    ) -> Num {
+        ucb_v_value(
+            parent_visits,
+            child.sum_rewards.get(turn),
+            child.sum_squared_rewards.get(turn),
+            child.n_visits,
+            args.c,
+            args.zeta,
+        )
+    }
+
+    fn evaluate_leaf(child: &State, _turn: &State::Player) -> Self::EvalResult {
+        RewardVec::from_outcome(&child.outcome(), child.score_bounds())
+    }
+}
+
+/// Evaluates the nodes with KL-UCB from the point of view of the player choosing between them, and
+/// the final states from their outcome like the `DefaultUctEvaluator`. The argument is the c of
+/// KL-UCB, 0 works well in practice.
+pub struct KlUcbEvaluator;
+
+impl<State: GameTrait, A: Clone + Default> Evaluator<State, RewardVec<State::Player>, A>
+for KlUcbEvaluator
+{
+    type Args = f64;
+    type EvalResult = RewardVec<State::Player>;
+
+    fn eval_child<T: Clone>(
+        child: &MctsNode<T, State::Move, RewardVec<State::Player>, A>,
+        turn: &State::Player,
+        parent_visits: Nat,
+        &c: &Self::Args,
+    ) -> Num {
+        kl_ucb_value(parent_visits, child.sum_rewards.get(turn), child.n_visits, c)
+    }
+
+    fn evaluate_leaf(child: &State, _turn: &State::Player) -> Self::EvalResult {
+        RewardVec::from_outcome(&child.outcome(), child.score_bounds())
+    }
+}
+
+/// Evaluates the nodes by sampling the Beta posterior of their mean reward for the player choosing
+/// between them (Thompson sampling), and the final states from their outcome like the
+/// `DefaultUctEvaluator`. The values are random, so `best_move` is a sample too: use
+/// `final_move(FinalMove::MostVisited, ..)` to choose the move to play.
+pub struct ThompsonEvaluator;
+
+impl<State: GameTrait, A: Clone + Default> Evaluator<State, RewardVec<State::Player>, A>
+for ThompsonEvaluator
+{
+    type Args = ();
+    type EvalResult = RewardVec<State::Player>;
+
+    fn eval_child<T: Clone>(
+        child: &MctsNode<T, State::Move, RewardVec<State::Player>, A>,
+        turn: &State::Player,
+        _parent_visits: Nat,
+        _args: &Self::Args,
+    ) -> Num {
+        let successes = child.sum_rewards.get(turn).max(0.);
+        let failures = (child.n_visits as f64 - successes).max(0.);
+        let posterior = Beta::new(1. + successes, 1. + failures).unwrap();
+        n64(posterior.sample(&mut search_rng()))
+    }
+
+    fn evaluate_leaf(child: &State, _turn: &State::Player) -> Self::EvalResult {
+        RewardVec::from_outcome(&child.outcome(), child.score_bounds())
+    }
+}
+
+#[test]
+fn test_search_with_each_evaluator_finds_winning_move() {
+    use crate::test_games::Guess;
+    use crate::{
+        DefaultBackProp, DefaultLazyTreePolicy, DefaultPlayout, FinalMove, LazyMcts, StoppingRule,
+    };
+
+    type Reward = RewardVec<u8>;
+    type GuessMcts<'a, EV> = LazyMcts<
+        'a,
+        Guess,
+        DefaultLazyTreePolicy<Guess, EV, (), Reward>,
+        DefaultPlayout,
+        DefaultBackProp,
+        EV,
+        (),
+        Reward,
+    >;
+
+    fn searched<'a, EV>(game: &'a Guess, args: &EV::Args) -> GuessMcts<'a, EV>
+        where
+            EV: Evaluator<Guess, Reward, (), EvalResult = Reward>,
+    {
+        let mut mcts = GuessMcts::<EV>::new(game);
+        mcts.search(300, &StoppingRule::Never, args, ());
+        mcts
+    }
+
+    let game = Guess(None);
+    let args = UcbVArgs::default();
+    assert_eq!(searched::<UcbVEvaluator>(&game, &args).best_move(&args), 5);
+    assert_eq!(searched::<KlUcbEvaluator>(&game, &0.).best_move(&0.), 5);
+    let mcts = searched::<ThompsonEvaluator>(&game, &());
+    assert_eq!(mcts.final_move(FinalMove::MostVisited, &()), 5);
+}
@@ -0,0 +1,331 @@
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use ego_tree::NodeId;
+
+use crate::aliases::{LazyMctsTree, MctsTree, Nat};
+use crate::mcts_node::move_path;
+use crate::reward::PlayerReward;
+use crate::traits::{Evaluator, GameTrait};
+
+/// Which nodes of a tree are exported.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ExportOptions {
+    /// The nodes deeper than this depth are not exported, the root is at depth 0.
+    pub max_depth: Option<usize>,
+    /// The nodes with less visits, and their subtrees, are not exported.
+    pub min_visits: Nat,
+}
+
+/// The principal variation of the tree, following the most visited child from the root.
+pub(crate) fn principal_variation<T, M, R, A>(tree: &MctsTree<T, M, R, A>) -> Vec<NodeId>
+    where
+        T: Clone,
+        M: Clone,
+        R: Clone,
+        A: Clone + Default,
+{
+    let mut variation = vec![];
+    let mut node = tree.root();
+    while let Some(child) = node.children().max_by_key(|c| c.value().n_visits) {
+        variation.push(child.id());
+        node = child;
+    }
+    variation
+}
+
+/// Writes the tree in the DOT format of Graphviz. Each node is labeled with its move, its visits,
+/// its mean reward and its score given by `EV`, both from the point of view of the player choosing
+/// it. The principal variation is highlighted in red.
+pub(crate) fn tree_to_dot<State, EV, R, A>(
+    tree: &LazyMctsTree<State, R, A>,
+    root_state: &State,
+    options: &ExportOptions,
+    evaluator_args: &EV::Args,
+) -> String
+    where
+        State: GameTrait,
+        EV: Evaluator<State, R, A>,
+        R: Clone + PlayerReward<State::Player>,
+        A: Clone + Default,
+{
+    let variation: HashSet<NodeId> = principal_variation(tree).into_iter().collect();
+    let mut output = String::from("digraph mcts {\n    node [shape=box];\n");
+    writeln!(
+        output,
+        "    n0 [label=\"root\\nvisits: {}\", color=red, penwidth=2];",
+        tree.root().value().n_visits
+    )
+    .unwrap();
+    let mut writer = DotWriter::<State, EV, R, A> {
+        tree,
+        options,
+        evaluator_args,
+        variation,
+        output,
+        n_nodes: 1,
+    };
+    writer.write_children(tree.root().id(), root_state, 0, 0);
+    writer.output.push_str("}\n");
+    writer.output
+}
+
+struct DotWriter<'a, State, EV, R, A>
+    where
+        State: GameTrait,
+        EV: Evaluator<State, R, A>,
+        R: Clone,
+        A: Clone + Default,
+{
+    tree: &'a LazyMctsTree<State, R, A>,
+    options: &'a ExportOptions,
+    evaluator_args: &'a EV::Args,
+    variation: HashSet<NodeId>,
+    output: String,
+    n_nodes: usize,
+}
+
+impl<State, EV, R, A> DotWriter<'_, State, EV, R, A>
+    where
+        State: GameTrait,
+        EV: Evaluator<State, R, A>,
+        R: Clone + PlayerReward<State::Player>,
+        A: Clone + Default,
+{
+    /// Writes the children of the node named `name`, `state` being its state.
+    fn write_children(&mut self, node_id: NodeId, state: &State, name: usize, depth: usize) {
+        if self.options.max_depth.is_some_and(|max_depth| depth >= max_depth) {
+            return;
+        }
+        let node = self.tree.get(node_id).unwrap();
+        let turn = state.player_turn();
+        let parent_visits = node.value().n_visits;
+        for child in node.children() {
+            let value = child.value();
+            if value.n_visits < self.options.min_visits {
+                continue;
+            }
+            let child_name = self.n_nodes;
+            self.n_nodes += 1;
+            let m = value.incoming_move().expect("A child always has an incoming move");
+            let mean = if value.n_visits == 0 {
+                0.
+            } else {
+                value.sum_rewards.reward_of(&turn) / value.n_visits as f64
+            };
+            let score = EV::eval_child(value, &turn, parent_visits, self.evaluator_args);
+            let style = if self.variation.contains(&child.id()) {
+                ", color=red, penwidth=2"
+            } else {
+                ""
+            };
+            writeln!(
+                self.output,
+                "    n{} [label=\"{}\\nvisits: {}\\nmean: {:.3}\\nscore: {:.3}\"{}];",
+                child_name,
+                escape(&format!("{:?}", m)),
+                value.n_visits,
+                mean,
+                score.raw(),
+                style
+            )
+            .unwrap();
+            let edge_style = style.trim_start_matches(", ");
+            writeln!(self.output, "    n{} -> n{} [{}];", name, child_name, edge_style).unwrap();
+            let mut child_state = state.clone();
+            child_state.do_move(m);
+            self.write_children(child.id(), &child_state, child_name, depth + 1);
+        }
+    }
+}
+
+/// Writes the subtree of `node_id` in JSON, see `LazyMcts::write_json` for the format.
+pub(crate) fn tree_to_json<State, R, A>(
+    tree: &LazyMctsTree<State, R, A>,
+    root_state: &State,
+    node_id: NodeId,
+    options: &ExportOptions,
+    serialize_info: &dyn Fn(&A) -> String,
+) -> String
+    where
+        State: GameTrait,
+        R: Clone + PlayerReward<State::Player>,
+        A: Clone + Default,
+{
+    let mut path = move_path(tree, node_id);
+    let last_move = path.pop();
+    let mut state = root_state.clone();
+    for m in &path {
+        state.do_move(m);
+    }
+    let chooser = state.player_turn();
+    if let Some(m) = last_move {
+        state.do_move(&m);
+    }
+    let mut writer = JsonWriter {
+        tree,
+        options,
+        serialize_info,
+        output: String::from("{\"version\":1,\"root\":"),
+    };
+    writer.write_node(node_id, &state, &chooser, 0);
+    writer.output.push('}');
+    writer.output
+}
+
+struct JsonWriter<'a, State, R, A>
+    where
+        State: GameTrait,
+        R: Clone,
+        A: Clone + Default,
+{
+    tree: &'a LazyMctsTree<State, R, A>,
+    options: &'a ExportOptions,
+    serialize_info: &'a dyn Fn(&A) -> String,
+    output: String,
+}
+
+impl<State, R, A> JsonWriter<'_, State, R, A>
+    where
+        State: GameTrait,
+        R: Clone + PlayerReward<State::Player>,
+        A: Clone + Default,
+{
+    /// Writes the node and its subtree, `state` is its state and `chooser` the player choosing it.
+    fn write_node(
+        &mut self,
+        node_id: NodeId,
+        state: &State,
+        chooser: &State::Player,
+        depth: usize,
+    ) {
+        let node = self.tree.get(node_id).unwrap();
+        let value = node.value();
+        let m = match value.incoming_move() {
+            Some(m) => json_string(&format!("{:?}", m)),
+            None => "null".to_string(),
+        };
+        write!(
+            self.output,
+            "{{\"move\":{},\"visits\":{},\"reward\":{},\"hash\":\"{}\",\"info\":{},\
+             \"children\":[",
+            m,
+            value.n_visits,
+            json_number(value.sum_rewards.reward_of(chooser)),
+            value.hash,
+            (self.serialize_info)(&value.additional_info)
+        )
+        .unwrap();
+        if self.options.max_depth.map_or(true, |max_depth| depth < max_depth) {
+            let turn = state.player_turn();
+            let min_visits = self.options.min_visits;
+            let children = node.children().filter(|c| c.value().n_visits >= min_visits);
+            for (i, child) in children.enumerate() {
+                if i > 0 {
+                    self.output.push(',');
+                }
+                let mut child_state = state.clone();
+                child_state.do_move(child.value().incoming_move().unwrap());
+                self.write_node(child.id(), &child_state, &turn, depth + 1);
+            }
+        }
+        self.output.push_str("]}");
+    }
+}
+
+/// Escapes a string to be put between double quotes in a DOT label. The newlines become line
+/// breaks and the other control characters are written as their code, like in `json_string`.
+fn escape(s: &str) -> String {
+    let mut output = String::new();
+    for c in s.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            c if (c as u32) < 0x20 => write!(output, "\\\\u{:04x}", c as u32).unwrap(),
+            c => output.push(c),
+        }
+    }
+    output
+}
+
+/// A JSON number, or null if `x` isn't finite since JSON has no infinities nor NaN.
+fn json_number(x: f64) -> String {
+    if x.is_finite() {
+        x.to_string()
+    } else {
+        "null".to_string()
+    }
+}
+
+/// A JSON string.
+fn json_string(s: &str) -> String {
+    let mut output = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            c if (c as u32) < 0x20 => write!(output, "\\u{:04x}", c as u32).unwrap(),
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+    output
+}
+
+#[test]
+fn test_json_number() {
+    assert_eq!(json_number(1.5), "1.5");
+    assert_eq!(json_number(f64::NEG_INFINITY), "null");
+    assert_eq!(json_number(f64::NAN), "null");
+}
+
+#[test]
+fn test_escape() {
+    assert_eq!(escape("a\"b\\c"), "a\\\"b\\\\c");
+    assert_eq!(escape("a\nb\tc"), "a\\nb\\\\u0009c");
+}
+
+#[test]
+fn test_write_dot() {
+    use crate::test_games::Race;
+    use crate::DefaultMcts;
+
+    let game = Race(0);
+    let mut mcts = DefaultMcts::new(&game);
+    for _ in 0..300 {
+        mcts.execute(&1., ());
+    }
+    let options = ExportOptions { max_depth: Some(2), min_visits: 0 };
+    let dot = mcts.write_dot(&options, &1.);
+    assert!(dot.starts_with("digraph mcts {") && dot.ends_with("}\n"));
+    assert_eq!(dot.matches("->").count(), 3 + 9);
+    assert_eq!(dot.matches("color=red").count(), 1 + 2 * 2);
+    assert_eq!(mcts.principal_variation().len(), principal_variation(mcts.tree()).len());
+
+    let options = ExportOptions { max_depth: None, min_visits: 301 };
+    assert_eq!(mcts.write_dot(&options, &1.).matches("->").count(), 0);
+}
+
+#[test]
+fn test_write_json() {
+    use crate::test_games::Choice;
+    use crate::DefaultMcts;
+
+    let game = Choice(None);
+    let mut mcts = DefaultMcts::new(&game);
+    mcts.execute(&1., ());
+    mcts.execute(&1., ());
+    let json = mcts.write_json(mcts.tree().root().id(), &Default::default(), |_| "null".into());
+    let hash = u64::MAX;
+    let child = |m, reward| {
+        format!("{{\"move\":\"{}\",\"visits\":1,\"reward\":{},\"hash\":\"{}\",\"info\":null,\
+                 \"children\":[]}}", m, reward, hash)
+    };
+    let root = |children: String| {
+        format!("{{\"version\":1,\"root\":{{\"move\":null,\"visits\":2,\"reward\":1,\
+                 \"hash\":\"{}\",\"info\":null,\"children\":[{}]}}}}", hash, children)
+    };
+    assert!(json == root(format!("{},{}", child(0, 0), child(1, 1)))
+        || json == root(format!("{},{}", child(1, 1), child(0, 0))));
+}
@@ -1,31 +1,143 @@
 pub use crate::agents::*;
 pub use crate::aliases::*;
-use crate::defaults::DefaultUctEvaluator;
-pub use crate::defaults::{DefaultBackProp, DefaultLazyTreePolicy, DefaultPlayout};
+pub use crate::builder::{BuilderTreePolicy, MctsBuilder, MctsSearch};
+pub use crate::defaults::{
+    DefaultBackProp, DefaultLazyTreePolicy, DefaultPlayout, DefaultUctEvaluator,
+};
+pub use crate::dynamic::{
+    DefaultDynTreePolicy, DynBackProp, DynEvaluator, DynMcts, DynPlayout, DynTreePolicy,
+    StaticBackProp, StaticEvaluator, StaticPlayout,
+};
+pub use crate::eager::{state_of, CachedState, DefaultEagerTreePolicy, EagerMcts};
+pub use crate::export::ExportOptions;
+pub use crate::evaluators::{
+    ExplorationArgs, ExplorationEvaluator, HeuristicEvaluator, KlUcbEvaluator, ThompsonEvaluator,
+    Ucb1TunedEvaluator, UcbVArgs, UcbVEvaluator,
+};
 pub use crate::mcts_node::*;
+pub use crate::memory::{MemoryBudget, MemoryLimit, MemoryPolicy, MemoryUsage};
+pub use crate::nested::{nested_monte_carlo_search, nested_rollout_policy_adaptation};
 pub use crate::ops::*;
+pub use crate::playouts::{
+    CutoffArgs, CutoffPlayout, EpsilonGreedyPlayout, LastGoodReplyPlayout, MastPlayout, MoveStats,
+    NstPlayout, PpaPlayout, SoftmaxPlayout,
+};
+pub use crate::ponder::Ponderer;
+pub use crate::reward::{PlayerReward, RewardVec};
+pub use crate::score_bounded::{ScoreBoundedBackProp, ScoreBoundedTreePolicy, ScoreBounds};
+#[cfg(feature = "serialize")]
+pub use crate::snapshot::{SnapshotError, TreeSnapshot};
+pub use crate::sp_mcts::{SpMctsArgs, SpMctsBackProp, SpMctsEvaluator, SpMctsInfo, SpMctsPlayout};
+pub use crate::stats::SearchStats;
+pub use crate::stopping::{CancellationToken, SearchReport, StoppingRule};
 pub use crate::traits::*;
-pub use crate::tree_search::LazyMcts;
+pub use crate::tree_search::{FinalMove, GumbelArgs, LazyMcts};
 pub use ego_tree::*;
 
 mod agents;
 mod aliases;
+mod builder;
 mod defaults;
+mod dynamic;
+mod eager;
+mod evaluators;
+mod export;
 mod mcts_node;
+mod memory;
+mod nested;
 mod ops;
+mod playouts;
+mod ponder;
+mod reward;
+mod rng;
+mod score_bounded;
+mod search_core;
+#[cfg(feature = "serialize")]
+mod snapshot;
+mod sp_mcts;
+mod stats;
+mod stopping;
+#[cfg(test)]
+mod test_games;
 mod traits;
 mod tree_search;
 
 /// This mcts uses UCT, naive simulation applying random moves until a final state, and scoring 1
-/// if the player won.
+/// for the winner and 0.5 for a draw. Each node is evaluated from the point of view of the player
+/// choosing it.
 pub type DefaultMcts<'a, State> = LazyMcts<
     'a,
     State,
-    DefaultLazyTreePolicy<State, DefaultUctEvaluator, (), u32>,
+    DefaultLazyTreePolicy<
+        State,
+        DefaultUctEvaluator,
+        (),
+        RewardVec<<State as GameTrait>::Player>,
+    >,
     DefaultPlayout,
     DefaultBackProp,
     DefaultUctEvaluator,
     (),
-    u32,
+    RewardVec<<State as GameTrait>::Player>,
 >;
 
+/// Same as the `DefaultMcts` but the states are stored in the nodes, see `EagerMcts`.
+pub type DefaultEagerMcts<State> = EagerMcts<
+    State,
+    DefaultEagerTreePolicy<
+        State,
+        DefaultUctEvaluator,
+        (),
+        RewardVec<<State as GameTrait>::Player>,
+    >,
+    DefaultPlayout,
+    DefaultBackProp,
+    DefaultUctEvaluator,
+    (),
+    RewardVec<<State as GameTrait>::Player>,
+>;
+
+/// Same as the `DefaultMcts` but each node keeps bounds on the final reward of the root player,
+/// that are used to prune the selection. It solves the game when the tree is big enough.
+pub type ScoreBoundedMcts<'a, State> = LazyMcts<
+    'a,
+    State,
+    ScoreBoundedTreePolicy<State, DefaultUctEvaluator, RewardVec<<State as GameTrait>::Player>>,
+    DefaultPlayout,
+    ScoreBoundedBackProp,
+    DefaultUctEvaluator,
+    ScoreBounds,
+    RewardVec<<State as GameTrait>::Player>,
+>;
+
+/// Same as the `DefaultMcts` but the playouts are cut off at a given depth, or when the heuristic
+/// of the game is decisive, and then evaluated with the heuristic.
+pub type HeuristicMcts<'a, State> = LazyMcts<
+    'a,
+    State,
+    DefaultLazyTreePolicy<
+        State,
+        HeuristicEvaluator<DefaultUctEvaluator>,
+        (),
+        RewardVec<<State as GameTrait>::Player>,
+    >,
+    CutoffPlayout,
+    DefaultBackProp,
+    HeuristicEvaluator<DefaultUctEvaluator>,
+    (),
+    RewardVec<<State as GameTrait>::Player>,
+>;
+
+/// Single-Player MCTS, for puzzles and optimization problems. The nodes keep the best score and
+/// the variance of their scores, which are used by the selection, and the best sequence of moves
+/// found is recorded by the playout policy.
+pub type SpMcts<'a, State> = LazyMcts<
+    'a,
+    State,
+    DefaultLazyTreePolicy<State, SpMctsEvaluator, SpMctsInfo, f64>,
+    SpMctsPlayout<State>,
+    SpMctsBackProp,
+    SpMctsEvaluator,
+    SpMctsInfo,
+    f64,
+>;
@@ -1,12 +1,13 @@
 use std::fmt::{Debug, Error, Formatter};
-use std::ops::{Add, Deref, DerefMut, Div};
+use std::ops::{Deref, DerefMut};
 
-use num_traits::{ToPrimitive, Zero};
+use ego_tree::NodeId;
 
-use crate::aliases::Nat;
+use crate::aliases::{MctsTree, Nat};
 use crate::traits::GameTrait;
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct MctsNode<T, Move, Reward, AdditionalInfo = ()>
 where
     Reward: Clone,
@@ -15,6 +16,8 @@ where
     AdditionalInfo: Clone + Default,
 {
     pub sum_rewards: Reward,
+    /// Used by the evaluators needing the variance of the rewards.
+    pub sum_squared_rewards: Reward,
     pub n_visits: Nat,
     /// All the moves who don't have a node. at the creation this list contains all the legals
     /// moves from the state.
@@ -42,9 +45,39 @@ where
     }
 }
 
+impl<M, R, A> MctsNode<Option<M>, M, R, A>
+where
+    R: Clone,
+    M: Clone,
+    A: Clone + Default,
+{
+    /// The move leading to this node of a lazy tree, `None` for the root.
+    #[inline]
+    pub fn incoming_move(&self) -> Option<&M> {
+        self.state.as_ref()
+    }
+}
+
+/// The moves leading from the root of a lazy tree to the node, rebuilt from the incoming moves of
+/// the node and its ancestors.
+pub fn move_path<M, R, A>(tree: &MctsTree<Option<M>, M, R, A>, node_id: NodeId) -> Vec<M>
+where
+    M: Clone,
+    R: Clone,
+    A: Clone + Default,
+{
+    let node = tree.get(node_id).unwrap();
+    let mut path: Vec<M> = std::iter::once(node)
+        .chain(node.ancestors())
+        .filter_map(|n| n.value().incoming_move().cloned())
+        .collect();
+    path.reverse();
+    path
+}
+
 impl<T, M, R, A> Deref for MctsNode<T, M, R, A>
 where
-    R: Clone + Add + Div + ToPrimitive + Zero,
+    R: Clone,
     T: Clone + GameTrait,
     M: Clone,
     A: Clone + Default,
@@ -58,7 +91,7 @@ where
 
 impl<T, M, R, A> DerefMut for MctsNode<T, M, R, A>
 where
-    R: Clone + Add + Div + Zero + ToPrimitive,
+    R: Clone,
     T: Clone + GameTrait,
     M: Clone,
     A: Clone + Default,
@@ -70,7 +103,7 @@ where
 
 impl<T, M, R, A> Debug for MctsNode<T, M, R, A>
 where
-    R: Clone + Add + Div + ToPrimitive + Zero,
+    R: Clone,
     T: Clone,
     M: Clone,
     A: Clone + Default,
@@ -86,7 +119,7 @@ where
 /// Unstable
 impl<T, M, R, A> PartialEq for MctsNode<T, M, R, A>
 where
-    R: Clone + Add + Div + Zero + ToPrimitive,
+    R: Clone,
     T: Clone,
     M: Clone,
     A: Clone + Default,
@@ -95,3 +128,21 @@ where
         self.hash == other.hash
     }
 }
+
+#[test]
+fn test_move_path() {
+    let node = |m| MctsNode::<Option<u8>, u8, f64> {
+        sum_rewards: 0.,
+        sum_squared_rewards: 0.,
+        n_visits: 0,
+        unvisited_moves: vec![],
+        hash: 0,
+        state: m,
+        additional_info: (),
+    };
+    let mut tree = ego_tree::Tree::new(node(None));
+    let child = tree.root_mut().append(node(Some(1))).id();
+    let grandchild = tree.get_mut(child).unwrap().append(node(Some(2))).id();
+    assert_eq!(move_path(&tree, tree.root().id()), Vec::<u8>::new());
+    assert_eq!(move_path(&tree, grandchild), vec![1, 2]);
+}
@@ -0,0 +1,115 @@
+use std::mem::size_of;
+
+use crate::aliases::LazyMctsNode;
+use crate::traits::GameTrait;
+
+/// The maximum size of a tree.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MemoryBudget {
+    /// The maximum number of nodes.
+    Nodes(usize),
+    /// The maximum number of bytes, estimated from the size of the nodes and of their unvisited
+    /// moves.
+    Bytes(usize),
+}
+
+/// What the search does when the tree reaches its memory budget.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MemoryPolicy {
+    /// The tree stops growing, the iterations go down to a leaf and simulate from it.
+    StopExpanding,
+    /// The subtrees visited the least recently are pruned.
+    RecycleLeastRecentlyVisited,
+    /// The subtrees with the fewest visits are pruned.
+    RecycleLeastVisited,
+}
+
+/// Bounds the memory used by a search. When recycling, the subtrees are pruned until the tree
+/// uses three quarters of its budget, so the pruning doesn't happen at each iteration. The
+/// children of the root are never pruned: when nothing else is left to prune, the tree stops
+/// expanding as with `StopExpanding` until it is under its budget again.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MemoryLimit {
+    pub budget: MemoryBudget,
+    pub policy: MemoryPolicy,
+}
+
+impl MemoryLimit {
+    /// The tree has reached its budget.
+    pub fn is_reached(&self, usage: &MemoryUsage) -> bool {
+        match self.budget {
+            MemoryBudget::Nodes(nodes) => usage.nodes >= nodes,
+            MemoryBudget::Bytes(bytes) => usage.bytes >= bytes,
+        }
+    }
+
+    /// Enough subtrees have been pruned.
+    pub(crate) fn is_recycled(&self, usage: &MemoryUsage) -> bool {
+        match self.budget {
+            MemoryBudget::Nodes(nodes) => usage.nodes <= nodes * 3 / 4,
+            MemoryBudget::Bytes(bytes) => usage.bytes <= bytes * 3 / 4,
+        }
+    }
+}
+
+/// The size of a tree, and the number of nodes pruned since the beginning of the search.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MemoryUsage {
+    pub nodes: usize,
+    /// Estimated, see `MemoryBudget::Bytes`.
+    pub bytes: usize,
+    pub pruned_nodes: usize,
+}
+
+/// Estimated size of a node in bytes.
+pub(crate) fn node_bytes<State, R, A>(node: &LazyMctsNode<State, R, A>) -> usize
+    where
+        State: GameTrait,
+        R: Clone,
+        A: Clone + Default,
+{
+    size_of::<LazyMctsNode<State, R, A>>()
+        + node.unvisited_moves.capacity() * size_of::<State::Move>()
+}
+
+#[test]
+fn test_memory_limit_bounds_the_tree() {
+    use crate::test_games::Race;
+    use crate::{DefaultMcts, MemoryBudget::Nodes};
+
+    let game = Race(0);
+    for &policy in &[
+        MemoryPolicy::StopExpanding,
+        MemoryPolicy::RecycleLeastRecentlyVisited,
+        MemoryPolicy::RecycleLeastVisited,
+    ] {
+        let mut mcts = DefaultMcts::new(&game);
+        mcts.set_memory_limit(Some(MemoryLimit { budget: Nodes(50), policy }));
+        for _ in 0..500 {
+            mcts.execute(&1., ());
+        }
+        let usage = mcts.memory_usage();
+        assert_eq!(mcts.tree().root().descendants().count(), usage.nodes);
+        assert!(usage.nodes <= 50);
+        assert_eq!(usage.pruned_nodes > 0, policy != MemoryPolicy::StopExpanding);
+        assert_eq!(mcts.tree().root().value().n_visits, 500);
+    }
+}
+
+#[test]
+fn test_recycling_stops_expanding_when_only_the_root_children_are_left() {
+    use crate::test_games::Race;
+    use crate::{DefaultMcts, MemoryBudget::Nodes};
+
+    let game = Race(0);
+    let mut mcts = DefaultMcts::new(&game);
+    let limit = MemoryLimit { budget: Nodes(3), policy: MemoryPolicy::RecycleLeastVisited };
+    mcts.set_memory_limit(Some(limit));
+    for _ in 0..100 {
+        mcts.execute(&1., ());
+    }
+    let usage = mcts.memory_usage();
+    assert_eq!(usage.nodes, 3);
+    assert_eq!(usage.pruned_nodes, 0);
+    assert_eq!(mcts.tree().root().value().n_visits, 100);
+}
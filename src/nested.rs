@@ -0,0 +1,250 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use rand::prelude::SliceRandom;
+
+use crate::playouts::gibbs_index;
+use crate::rng::search_rng;
+use crate::traits::SinglePlayerGame;
+
+/// Plays random moves until the end or until no move is legal, returns the moves played and the
+/// final score.
+fn random_playout<G: SinglePlayerGame>(mut state: G) -> (Vec<G::Move>, f64) {
+    let mut played = vec![];
+    while !state.is_final() {
+        let m = match state.legals_moves().choose(&mut search_rng()) {
+            Some(m) => m.clone(),
+            None => break,
+        };
+        state.do_move(&m);
+        played.push(m);
+    }
+    (played, state.score())
+}
+
+/// Nested Monte Carlo Search. At each step every move is tried with a search of the level below,
+/// and the best sequence found so far is followed. The level 0 is a random playout.
+/// Returns the best sequence of moves found from the state and its score.
+pub fn nested_monte_carlo_search<G: SinglePlayerGame>(
+    state: &G,
+    level: usize,
+) -> (Vec<G::Move>, f64) {
+    if level == 0 {
+        return random_playout(state.clone());
+    }
+    let mut state = state.clone();
+    let mut played = vec![];
+    let mut best_sequence = vec![];
+    let mut best_score = f64::NEG_INFINITY;
+    while !state.is_final() {
+        let moves = state.legals_moves();
+        if moves.is_empty() {
+            break;
+        }
+        for m in moves {
+            let mut child = state.clone();
+            child.do_move(&m);
+            let (sequence, score) = nested_monte_carlo_search(&child, level - 1);
+            // The first sequence of the step is always recorded, even with a NaN score.
+            if best_sequence.len() <= played.len() || score > best_score {
+                best_score = score;
+                best_sequence = played.clone();
+                best_sequence.push(m);
+                best_sequence.extend(sequence);
+            }
+        }
+        let m = best_sequence[played.len()].clone();
+        state.do_move(&m);
+        played.push(m);
+    }
+    (played, state.score())
+}
+
+/// The policy of the NRPA, the weight of each move.
+type NrpaPolicy<M> = HashMap<M, f64>;
+
+fn nrpa_weight<M: Hash + Eq>(policy: &NrpaPolicy<M>, m: &M) -> f64 {
+    policy.get(m).cloned().unwrap_or(0.)
+}
+
+/// Plays the moves with a probability proportional to exp(weight).
+fn nrpa_playout<G>(mut state: G, policy: &NrpaPolicy<G::Move>) -> (Vec<G::Move>, f64)
+    where
+        G: SinglePlayerGame,
+        G::Move: Hash + Eq,
+{
+    let mut rng = search_rng();
+    let mut played = vec![];
+    while !state.is_final() {
+        let mut moves = state.legals_moves();
+        if moves.is_empty() {
+            break;
+        }
+        let weights: Vec<f64> = moves.iter().map(|m| nrpa_weight(policy, m)).collect();
+        let m = moves.swap_remove(gibbs_index(&weights, 1., &mut rng));
+        state.do_move(&m);
+        played.push(m);
+    }
+    (played, state.score())
+}
+
+/// Moves the policy toward the sequence of moves.
+fn nrpa_adapt<G>(mut state: G, policy: &mut NrpaPolicy<G::Move>, sequence: &[G::Move], alpha: f64)
+    where
+        G: SinglePlayerGame,
+        G::Move: Hash + Eq,
+{
+    let old_policy = policy.clone();
+    for m in sequence {
+        let legals = state.legals_moves();
+        let z: f64 = legals.iter().map(|l| nrpa_weight(&old_policy, l).exp()).sum();
+        for l in legals {
+            let probability = nrpa_weight(&old_policy, &l).exp() / z;
+            *policy.entry(l).or_insert(0.) -= alpha * probability;
+        }
+        *policy.entry(m.clone()).or_insert(0.) += alpha;
+        state.do_move(m);
+    }
+}
+
+fn nrpa_level<G>(
+    state: &G,
+    level: usize,
+    iterations: usize,
+    alpha: f64,
+    mut policy: NrpaPolicy<G::Move>,
+) -> (Vec<G::Move>, f64)
+    where
+        G: SinglePlayerGame,
+        G::Move: Hash + Eq,
+{
+    if level == 0 {
+        return nrpa_playout(state.clone(), &policy);
+    }
+    let mut best_sequence = vec![];
+    let mut best_score = f64::NEG_INFINITY;
+    for i in 0..iterations {
+        let (sequence, score) = nrpa_level(state, level - 1, iterations, alpha, policy.clone());
+        if i == 0 || score >= best_score {
+            best_score = score;
+            best_sequence = sequence;
+        }
+        nrpa_adapt(state.clone(), &mut policy, &best_sequence, alpha);
+    }
+    (best_sequence, best_score)
+}
+
+/// Nested Rollout Policy Adaptation. Each level runs `iterations` searches of the level below, and
+/// after each of them adapts the policy toward the best sequence found with the learning rate
+/// `alpha`. The level 0 is a playout choosing the moves with a probability proportional to
+/// exp(weight of the move). Returns the best sequence of moves found from the state and its score.
+pub fn nested_rollout_policy_adaptation<G>(
+    state: &G,
+    level: usize,
+    iterations: usize,
+    alpha: f64,
+) -> (Vec<G::Move>, f64)
+    where
+        G: SinglePlayerGame,
+        G::Move: Hash + Eq,
+{
+    nrpa_level(state, level, iterations, alpha, HashMap::new())
+}
+
+#[test]
+fn test_nested_searches_find_the_best_product() {
+    use crate::traits::Outcome;
+
+    /// Two numbers between 0 and 2 are chosen, the score is their product.
+    #[derive(Clone)]
+    struct Product(Vec<u8>);
+
+    impl crate::traits::GameTrait for Product {
+        type Player = ();
+        type Move = (usize, u8);
+
+        fn legals_moves(&self) -> Vec<(usize, u8)> {
+            (0..3).map(|x| (self.0.len(), x)).collect()
+        }
+
+        fn player_turn(&self) {}
+
+        fn hash(&self) -> u64 {
+            0
+        }
+
+        fn is_final(&self) -> bool {
+            self.0.len() == 2
+        }
+
+        fn do_move(&mut self, m: &(usize, u8)) {
+            self.0.push(m.1)
+        }
+
+        fn outcome(&self) -> Outcome<()> {
+            Outcome::Scores(vec![((), self.score())])
+        }
+    }
+
+    impl SinglePlayerGame for Product {
+        fn score(&self) -> f64 {
+            self.0.iter().map(|&x| x as f64).product()
+        }
+    }
+
+    let (sequence, score) = nested_monte_carlo_search(&Product(vec![]), 2);
+    assert_eq!((sequence, score), (vec![(0, 2), (1, 2)], 4.));
+    let (sequence, score) = nested_rollout_policy_adaptation(&Product(vec![]), 2, 30, 1.);
+    assert_eq!((sequence, score), (vec![(0, 2), (1, 2)], 4.));
+}
+
+#[test]
+fn test_nested_searches_stop_without_legal_moves() {
+    use crate::traits::Outcome;
+
+    /// A single move is legal, then the game is stuck without being final, with a score of -inf.
+    #[derive(Clone)]
+    struct Stuck(bool);
+
+    impl crate::traits::GameTrait for Stuck {
+        type Player = ();
+        type Move = ();
+
+        fn legals_moves(&self) -> Vec<()> {
+            if self.0 {
+                vec![]
+            } else {
+                vec![()]
+            }
+        }
+
+        fn player_turn(&self) {}
+
+        fn hash(&self) -> u64 {
+            0
+        }
+
+        fn is_final(&self) -> bool {
+            false
+        }
+
+        fn do_move(&mut self, _m: &()) {
+            self.0 = true
+        }
+
+        fn outcome(&self) -> Outcome<()> {
+            Outcome::Scores(vec![((), self.score())])
+        }
+    }
+
+    impl SinglePlayerGame for Stuck {
+        fn score(&self) -> f64 {
+            f64::NEG_INFINITY
+        }
+    }
+
+    let (sequence, score) = nested_monte_carlo_search(&Stuck(false), 2);
+    assert_eq!((sequence, score), (vec![()], f64::NEG_INFINITY));
+    let (sequence, score) = nested_rollout_policy_adaptation(&Stuck(false), 2, 3, 1.);
+    assert_eq!((sequence, score), (vec![()], f64::NEG_INFINITY));
+}
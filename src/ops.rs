@@ -11,7 +11,92 @@ pub fn uct_value(parent_visits: Nat, sum_rewards: f64, node_visit: Nat, c: f64)
     exploitation_param + n64(c) * exploration_param
 }
 
+/// Calculates the UCB1-Tuned value, where the exploration uses an upper bound of the variance of
+/// the rewards, which must be in [0, 1].
+#[inline]
+pub fn ucb1_tuned_value(
+    parent_visits: Nat,
+    sum_rewards: f64,
+    sum_squared_rewards: f64,
+    node_visit: Nat,
+) -> Num {
+    let n = node_visit as f64;
+    let mean = sum_rewards / n;
+    let log_parent = (parent_visits as f64).ln();
+    let variance = (sum_squared_rewards / n - mean * mean).max(0.);
+    let variance_bound = variance + (2. * log_parent / n).sqrt();
+    n64(mean + (log_parent / n * variance_bound.min(0.25)).sqrt())
+}
+
+/// Calculates the UCB-V value, using the empirical variance of the rewards, which must be in
+/// [0, 1]. `zeta` scales the exploration and `c` the correction for the nodes with few visits.
+#[inline]
+pub fn ucb_v_value(
+    parent_visits: Nat,
+    sum_rewards: f64,
+    sum_squared_rewards: f64,
+    node_visit: Nat,
+    c: f64,
+    zeta: f64,
+) -> Num {
+    let n = node_visit as f64;
+    let mean = sum_rewards / n;
+    let variance = (sum_squared_rewards / n - mean * mean).max(0.);
+    let exploration = zeta * (parent_visits as f64).ln();
+    n64(mean + (2. * variance * exploration / n).sqrt() + c * 3. * exploration / n)
+}
+
+/// Kullback-Leibler divergence between two Bernoulli distributions.
+fn bernoulli_kl(p: f64, q: f64) -> f64 {
+    let p = p.clamp(1e-15, 1. - 1e-15);
+    let q = q.clamp(1e-15, 1. - 1e-15);
+    p * (p / q).ln() + (1. - p) * ((1. - p) / (1. - q)).ln()
+}
+
+/// Calculates the KL-UCB value for rewards following a Bernoulli distribution, it's the highest
+/// mean q such that visits * kl(mean, q) <= ln(parent visits) + c * ln(ln(parent visits)).
+#[inline]
+pub fn kl_ucb_value(parent_visits: Nat, sum_rewards: f64, node_visit: Nat, c: f64) -> Num {
+    let n = node_visit as f64;
+    let mean = (sum_rewards / n).min(1.);
+    let log_parent = (parent_visits as f64).ln();
+    let bound = (log_parent + c * log_parent.ln().max(0.)) / n;
+    let (mut low, mut high) = (mean, 1.);
+    for _ in 0..32 {
+        let q = (low + high) / 2.;
+        if bernoulli_kl(mean, q) > bound {
+            high = q;
+        } else {
+            low = q;
+        }
+    }
+    n64(low)
+}
+
 #[test]
 fn test_uct_value() {
     assert!((uct_value(500, 0., 10, 2.0_f64.sqrt()) - 1.339088).abs() < 0.00001)
 }
+
+#[test]
+fn test_kl_ucb_value() {
+    // The KL-UCB bound is tighter than the UCB1 (c = sqrt(2)) one.
+    let kl_ucb = kl_ucb_value(100, 8., 10, 0.);
+    assert!(kl_ucb > 0.8 && kl_ucb < uct_value(100, 8., 10, 2.0_f64.sqrt()));
+    assert!((bernoulli_kl(0.8, kl_ucb.raw()) * 10. - 100f64.ln()).abs() < 1e-6);
+}
+
+#[test]
+fn test_ucb1_tuned_value() {
+    // Rewards of 0 and 1 have the highest variance, the bound of 1/4 is used.
+    assert!((ucb1_tuned_value(100, 5., 5., 10) - 0.839307).abs() < 0.00001);
+    // Constant rewards explore less than the bound.
+    assert!((ucb1_tuned_value(1000, 500., 250., 1000) - 0.528494).abs() < 0.00001);
+}
+
+#[test]
+fn test_ucb_v_value() {
+    assert!((ucb_v_value(100, 5., 5., 10, 1., 1.2) - 2.683513).abs() < 0.00001);
+    // Without variance nor correction the value is the mean.
+    assert_eq!(ucb_v_value(100, 5., 2.5, 10, 0., 1.2), 0.5);
+}
@@ -0,0 +1,693 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+use rand::distributions::WeightedIndex;
+use rand::prelude::{Distribution, SliceRandom};
+use rand::Rng;
+
+use crate::aliases::{Nat, PlayedMoves};
+use crate::reward::RewardVec;
+use crate::rng::search_rng;
+use crate::traits::{GameTrait, HeuristicGame, MoveScorer, Playout};
+
+/// Arguments of the `CutoffPlayout`.
+#[derive(Clone, Copy, Debug)]
+pub struct CutoffArgs {
+    /// Maximum number of moves played during a playout.
+    pub depth: usize,
+    /// If set, the playout also stops as soon as the heuristic of the player to move is closer
+    /// than this margin to 0 or 1.
+    pub decisive_margin: Option<f64>,
+}
+
+/// Simulating taking random moves until the end, a depth cutoff or a decisive heuristic. The
+/// returned state may not be final, so it must be used with an evaluator using the heuristic like
+/// `HeuristicEvaluator`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CutoffPlayout;
+
+impl<T: HeuristicGame, E> Playout<T, E> for CutoffPlayout {
+    type Args = CutoffArgs;
+
+    fn playout(&mut self, mut state: T, args: CutoffArgs) -> (T, PlayedMoves<T>) {
+        let mut played = vec![];
+        for _ in 0..args.depth {
+            if state.is_final() {
+                break;
+            }
+            if let Some(margin) = args.decisive_margin {
+                let heuristic = state.heuristic(&state.player_turn());
+                if heuristic <= margin || heuristic >= 1. - margin {
+                    break;
+                }
+            }
+            let moves = state.legals_moves();
+            let m = moves.choose(&mut search_rng()).unwrap();
+            played.push((state.player_turn(), m.clone()));
+            state.do_move(m);
+        }
+        (state, played)
+    }
+}
+
+/// Returns the index of the best score.
+fn best_index(scores: impl Iterator<Item = f64>) -> usize {
+    scores
+        .enumerate()
+        .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+        .unwrap()
+        .0
+}
+
+/// Samples an index with a probability proportional to exp(score / temperature) (Gibbs
+/// distribution), with a temperature of 0 the best score is always chosen.
+pub(crate) fn gibbs_index<R: Rng>(scores: &[f64], temperature: f64, rng: &mut R) -> usize {
+    if temperature <= 0. {
+        return best_index(scores.iter().cloned());
+    }
+    let max = scores.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let weights = scores.iter().map(|s| ((s - max) / temperature).exp());
+    WeightedIndex::new(weights).unwrap().sample(rng)
+}
+
+/// Simulating taking the best move according to `S` with probability 1 - epsilon, and a random
+/// move otherwise. The argument is epsilon.
+pub struct EpsilonGreedyPlayout<S> {
+    phantom_scorer: PhantomData<S>,
+}
+
+impl<S> Default for EpsilonGreedyPlayout<S> {
+    fn default() -> Self {
+        Self { phantom_scorer: PhantomData }
+    }
+}
+
+impl<T: GameTrait, S: MoveScorer<T>, E> Playout<T, E> for EpsilonGreedyPlayout<S> {
+    type Args = f64;
+
+    fn playout(&mut self, mut state: T, epsilon: f64) -> (T, PlayedMoves<T>) {
+        let mut rng = search_rng();
+        let mut played = vec![];
+        while !state.is_final() {
+            let mut moves = state.legals_moves();
+            let index = if rng.gen_bool(epsilon) {
+                rng.gen_range(0..moves.len())
+            } else {
+                best_index(moves.iter().map(|m| S::score(&state, m)))
+            };
+            let m = moves.swap_remove(index);
+            played.push((state.player_turn(), m.clone()));
+            state.do_move(&m);
+        }
+        (state, played)
+    }
+}
+
+/// Simulating taking moves with a probability proportional to exp(score / temperature) (Boltzmann
+/// distribution), with the scores given by `S`. The argument is the temperature, with a
+/// temperature of 0 the best move is always played.
+pub struct SoftmaxPlayout<S> {
+    phantom_scorer: PhantomData<S>,
+}
+
+impl<S> Default for SoftmaxPlayout<S> {
+    fn default() -> Self {
+        Self { phantom_scorer: PhantomData }
+    }
+}
+
+impl<T: GameTrait, S: MoveScorer<T>, E> Playout<T, E> for SoftmaxPlayout<S> {
+    type Args = f64;
+
+    fn playout(&mut self, mut state: T, temperature: f64) -> (T, PlayedMoves<T>) {
+        let mut rng = search_rng();
+        let mut played = vec![];
+        while !state.is_final() {
+            let mut moves = state.legals_moves();
+            let scores: Vec<f64> = moves.iter().map(|m| S::score(&state, m)).collect();
+            let m = moves.swap_remove(gibbs_index(&scores, temperature, &mut rng));
+            played.push((state.player_turn(), m.clone()));
+            state.do_move(&m);
+        }
+        (state, played)
+    }
+}
+
+/// Sum of the rewards and number of playouts of a move, or of a sequence of moves.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MoveStats {
+    pub sum_rewards: f64,
+    pub n_visits: Nat,
+}
+
+impl MoveStats {
+    #[inline]
+    pub fn mean(&self) -> f64 {
+        self.sum_rewards / self.n_visits as f64
+    }
+
+    #[inline]
+    fn add(&mut self, reward: f64) {
+        self.sum_rewards += reward;
+        self.n_visits += 1;
+    }
+}
+
+/// Move-Average Sampling Technique, keeps for each move and player the average reward of the
+/// playouts where it was played, over the whole search. The moves of the playouts are chosen with
+/// a Gibbs distribution on these averages, the argument is the temperature.
+pub struct MastPlayout<T: GameTrait> {
+    stats: HashMap<(T::Player, T::Move), MoveStats>,
+    /// Value of the moves never played.
+    pub initial_value: f64,
+}
+
+impl<T: GameTrait> MastPlayout<T>
+    where
+        T::Player: Hash,
+        T::Move: Hash + Eq,
+{
+    pub fn new(initial_value: f64) -> Self {
+        Self {
+            stats: HashMap::new(),
+            initial_value,
+        }
+    }
+
+    /// Average reward of the move for the player.
+    pub fn move_value(&self, player: &T::Player, m: &T::Move) -> f64 {
+        self.stats
+            .get(&(player.clone(), m.clone()))
+            .map_or(self.initial_value, MoveStats::mean)
+    }
+
+    pub fn stats(&self) -> &HashMap<(T::Player, T::Move), MoveStats> {
+        &self.stats
+    }
+}
+
+/// Unknown moves are valued 1 to be tried first.
+impl<T: GameTrait> Default for MastPlayout<T>
+    where
+        T::Player: Hash,
+        T::Move: Hash + Eq,
+{
+    fn default() -> Self {
+        Self::new(1.)
+    }
+}
+
+impl<T: GameTrait> Playout<T, RewardVec<T::Player>> for MastPlayout<T>
+    where
+        T::Player: Hash,
+        T::Move: Hash + Eq,
+{
+    type Args = f64;
+
+    fn playout(&mut self, mut state: T, temperature: f64) -> (T, PlayedMoves<T>) {
+        let mut rng = search_rng();
+        let mut played = vec![];
+        while !state.is_final() {
+            let player = state.player_turn();
+            let mut moves = state.legals_moves();
+            let values: Vec<f64> = moves.iter().map(|m| self.move_value(&player, m)).collect();
+            let m = moves.swap_remove(gibbs_index(&values, temperature, &mut rng));
+            state.do_move(&m);
+            played.push((player, m));
+        }
+        (state, played)
+    }
+
+    fn backprop(
+        &mut self,
+        _historic: &[T::Move],
+        moves: &[(T::Player, T::Move)],
+        eval: &RewardVec<T::Player>,
+    ) {
+        for (player, m) in moves {
+            self.stats
+                .entry((player.clone(), m.clone()))
+                .or_default()
+                .add(eval.get(player));
+        }
+    }
+}
+
+/// N-gram Selection Technique, like MAST but the statistics are kept for the sequences of up to
+/// `max_length` consecutive moves. The value of a move is the average of the values of the
+/// sequences ending with it and played at least `min_visits` times. The moves of the playouts are
+/// chosen with a Gibbs distribution on these values, the argument is the temperature.
+pub struct NstPlayout<T: GameTrait> {
+    stats: HashMap<(T::Player, Vec<T::Move>), MoveStats>,
+    pub max_length: usize,
+    pub min_visits: Nat,
+    /// Value of the moves never played.
+    pub initial_value: f64,
+}
+
+impl<T: GameTrait> NstPlayout<T>
+    where
+        T::Player: Hash,
+        T::Move: Hash + Eq,
+{
+    pub fn new(max_length: usize, min_visits: Nat) -> Self {
+        Self {
+            stats: HashMap::new(),
+            max_length,
+            min_visits,
+            initial_value: 1.,
+        }
+    }
+
+    /// Value of the move for the player, after the sequence of moves `previous`.
+    pub fn move_value(&self, player: &T::Player, previous: &[T::Move], m: &T::Move) -> f64 {
+        let mut key = (player.clone(), vec![m.clone()]);
+        let (mut sum, mut n) = (0., 0);
+        for length in 1..=self.max_length.min(previous.len() + 1) {
+            if length > 1 {
+                key.1.insert(0, previous[previous.len() + 1 - length].clone());
+            }
+            match self.stats.get(&key) {
+                Some(stats) if stats.n_visits >= self.min_visits => {
+                    sum += stats.mean();
+                    n += 1;
+                }
+                // The longer sequences are even less played.
+                _ => break,
+            }
+        }
+        if n == 0 {
+            self.initial_value
+        } else {
+            sum / n as f64
+        }
+    }
+
+    pub fn stats(&self) -> &HashMap<(T::Player, Vec<T::Move>), MoveStats> {
+        &self.stats
+    }
+}
+
+/// Sequences of up to 3 moves played at least 7 times.
+impl<T: GameTrait> Default for NstPlayout<T>
+    where
+        T::Player: Hash,
+        T::Move: Hash + Eq,
+{
+    fn default() -> Self {
+        Self::new(3, 7)
+    }
+}
+
+impl<T: GameTrait> Playout<T, RewardVec<T::Player>> for NstPlayout<T>
+    where
+        T::Player: Hash,
+        T::Move: Hash + Eq,
+{
+    type Args = f64;
+
+    fn playout(&mut self, mut state: T, temperature: f64) -> (T, PlayedMoves<T>) {
+        let mut rng = search_rng();
+        let mut played = vec![];
+        let mut previous = vec![];
+        while !state.is_final() {
+            let player = state.player_turn();
+            let mut moves = state.legals_moves();
+            let values: Vec<f64> = moves
+                .iter()
+                .map(|m| self.move_value(&player, &previous, m))
+                .collect();
+            let m = moves.swap_remove(gibbs_index(&values, temperature, &mut rng));
+            state.do_move(&m);
+            previous.push(m.clone());
+            played.push((player, m));
+        }
+        (state, played)
+    }
+
+    fn backprop(
+        &mut self,
+        _historic: &[T::Move],
+        moves: &[(T::Player, T::Move)],
+        eval: &RewardVec<T::Player>,
+    ) {
+        for (i, (player, _)) in moves.iter().enumerate() {
+            let reward = eval.get(player);
+            for length in 1..=self.max_length.min(i + 1) {
+                let sequence = moves[i + 1 - length..=i].iter().map(|(_, m)| m.clone()).collect();
+                self.stats
+                    .entry((player.clone(), sequence))
+                    .or_default()
+                    .add(reward);
+            }
+        }
+    }
+}
+
+/// Returns the best reward of the players who played during the playout, or None if they are all
+/// equally rewarded (a draw) so nobody has to be learned from.
+fn winning_reward<P: Clone + Eq, M>(moves: &[(P, M)], eval: &RewardVec<P>) -> Option<f64> {
+    let rewards = moves.iter().map(|(p, _)| eval.get(p));
+    let (min, max) = rewards.fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), r| {
+        (min.min(r), max.max(r))
+    });
+    if min < max {
+        Some(max)
+    } else {
+        None
+    }
+}
+
+/// Playout Policy Adaptation, the moves of the playouts are chosen with a probability proportional
+/// to exp(weight) of the move for its player. After each playout the weights are updated toward
+/// the moves played by the winning players, with a learning rate `alpha`.
+pub struct PpaPlayout<T: GameTrait> {
+    weights: HashMap<(T::Player, T::Move), f64>,
+    /// The player, the legal moves and the index of the move played at each step of the last
+    /// playout.
+    last_playout: Vec<(T::Player, Vec<T::Move>, usize)>,
+    pub alpha: f64,
+}
+
+impl<T: GameTrait> PpaPlayout<T>
+    where
+        T::Player: Hash,
+        T::Move: Hash + Eq,
+{
+    pub fn new(alpha: f64) -> Self {
+        Self {
+            weights: HashMap::new(),
+            last_playout: vec![],
+            alpha,
+        }
+    }
+
+    /// The weight of the move for the player, 0 if it was never learned.
+    pub fn weight(&self, player: &T::Player, m: &T::Move) -> f64 {
+        self.weights
+            .get(&(player.clone(), m.clone()))
+            .cloned()
+            .unwrap_or(0.)
+    }
+
+    pub fn weights(&self) -> &HashMap<(T::Player, T::Move), f64> {
+        &self.weights
+    }
+}
+
+/// A learning rate of 0.32.
+impl<T: GameTrait> Default for PpaPlayout<T>
+    where
+        T::Player: Hash,
+        T::Move: Hash + Eq,
+{
+    fn default() -> Self {
+        Self::new(0.32)
+    }
+}
+
+impl<T: GameTrait> Playout<T, RewardVec<T::Player>> for PpaPlayout<T>
+    where
+        T::Player: Hash,
+        T::Move: Hash + Eq,
+{
+    type Args = ();
+
+    fn playout(&mut self, mut state: T, _args: ()) -> (T, PlayedMoves<T>) {
+        let mut rng = search_rng();
+        let mut played = vec![];
+        self.last_playout.clear();
+        while !state.is_final() {
+            let player = state.player_turn();
+            let moves = state.legals_moves();
+            let weights: Vec<f64> = moves.iter().map(|m| self.weight(&player, m)).collect();
+            let index = gibbs_index(&weights, 1., &mut rng);
+            state.do_move(&moves[index]);
+            played.push((player.clone(), moves[index].clone()));
+            self.last_playout.push((player, moves, index));
+        }
+        (state, played)
+    }
+
+    fn backprop(
+        &mut self,
+        _historic: &[T::Move],
+        moves: &[(T::Player, T::Move)],
+        eval: &RewardVec<T::Player>,
+    ) {
+        let best = match winning_reward(moves, eval) {
+            Some(best) => best,
+            None => return,
+        };
+        // The update uses the weights before the playout, so the deltas are applied at the end.
+        let mut deltas = vec![];
+        for (player, legals, index) in &self.last_playout {
+            if eval.get(player) < best {
+                continue;
+            }
+            let exp_weights: Vec<f64> =
+                legals.iter().map(|m| self.weight(player, m).exp()).collect();
+            let z: f64 = exp_weights.iter().sum();
+            for (i, (m, exp_weight)) in legals.iter().zip(exp_weights).enumerate() {
+                let target = if i == *index { 1. } else { 0. };
+                deltas.push(((player.clone(), m.clone()), self.alpha * (target - exp_weight / z)));
+            }
+        }
+        for (key, delta) in deltas {
+            *self.weights.entry(key).or_insert(0.) += delta;
+        }
+    }
+}
+
+/// Last Good Reply policy, remembers for each player the last reply to a move of the previous
+/// player which won a playout, and plays it in the next playouts when it's legal. The other moves
+/// are random. With `forgetting` (LGRF) the replies which lose a playout are forgotten.
+pub struct LastGoodReplyPlayout<T: GameTrait> {
+    replies: HashMap<(T::Player, T::Move), T::Move>,
+    pub forgetting: bool,
+}
+
+impl<T: GameTrait> LastGoodReplyPlayout<T>
+    where
+        T::Player: Hash,
+        T::Move: Hash + Eq,
+{
+    pub fn new(forgetting: bool) -> Self {
+        Self {
+            replies: HashMap::new(),
+            forgetting,
+        }
+    }
+
+    /// The reply of the player to the previous move, if any.
+    pub fn reply(&self, player: &T::Player, previous: &T::Move) -> Option<&T::Move> {
+        self.replies.get(&(player.clone(), previous.clone()))
+    }
+
+    pub fn replies(&self) -> &HashMap<(T::Player, T::Move), T::Move> {
+        &self.replies
+    }
+}
+
+/// LGRF, with forgetting.
+impl<T: GameTrait> Default for LastGoodReplyPlayout<T>
+    where
+        T::Player: Hash,
+        T::Move: Hash + Eq,
+{
+    fn default() -> Self {
+        Self::new(true)
+    }
+}
+
+impl<T: GameTrait> Playout<T, RewardVec<T::Player>> for LastGoodReplyPlayout<T>
+    where
+        T::Player: Hash,
+        T::Move: Hash + Eq,
+{
+    type Args = ();
+
+    fn playout(&mut self, state: T, args: ()) -> (T, PlayedMoves<T>) {
+        self.playout_from(&[], state, args)
+    }
+
+    /// The first move of the playout replies to the last move of `historic`.
+    fn playout_from(
+        &mut self,
+        historic: &[T::Move],
+        mut state: T,
+        _args: (),
+    ) -> (T, PlayedMoves<T>) {
+        let mut rng = search_rng();
+        let mut played: PlayedMoves<T> = vec![];
+        while !state.is_final() {
+            let player = state.player_turn();
+            let mut moves = state.legals_moves();
+            let previous = played.last().map(|(_, m)| m).or_else(|| historic.last());
+            let reply = previous
+                .and_then(|previous| self.reply(&player, previous))
+                .and_then(|reply| moves.iter().position(|m| m == reply));
+            let index = reply.unwrap_or_else(|| rng.gen_range(0..moves.len()));
+            let m = moves.swap_remove(index);
+            state.do_move(&m);
+            played.push((player, m));
+        }
+        (state, played)
+    }
+
+    fn backprop(
+        &mut self,
+        historic: &[T::Move],
+        moves: &[(T::Player, T::Move)],
+        eval: &RewardVec<T::Player>,
+    ) {
+        let best = match winning_reward(moves, eval) {
+            Some(best) => best,
+            None => return,
+        };
+        let previous_moves = historic.last().into_iter().chain(moves.iter().map(|(_, m)| m));
+        for (previous, (player, m)) in previous_moves.zip(&moves[historic.is_empty() as usize..]) {
+            let key = (player.clone(), previous.clone());
+            if eval.get(player) >= best {
+                self.replies.insert(key, m.clone());
+            } else if self.forgetting && self.replies.get(&key) == Some(m) {
+                self.replies.remove(&key);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_nst_sequences_values() {
+    use crate::traits::Outcome;
+
+    #[derive(Clone)]
+    struct Dummy;
+
+    impl GameTrait for Dummy {
+        type Player = u8;
+        type Move = u8;
+
+        fn legals_moves(&self) -> Vec<u8> {
+            vec![]
+        }
+
+        fn player_turn(&self) -> u8 {
+            0
+        }
+
+        fn hash(&self) -> u64 {
+            0
+        }
+
+        fn is_final(&self) -> bool {
+            true
+        }
+
+        fn do_move(&mut self, _m: &u8) {}
+
+        fn outcome(&self) -> Outcome<u8> {
+            Outcome::Draw
+        }
+    }
+
+    let mut nst = NstPlayout::<Dummy>::new(2, 1);
+    let moves = [(0, 1), (1, 2), (0, 3)];
+    Playout::backprop(&mut nst, &[], &moves, &RewardVec::single(0, 1.));
+    Playout::backprop(&mut nst, &[], &[(1, 2)], &RewardVec::single(1, 1.));
+    assert_eq!(nst.stats().len(), 5);
+    // The 1-gram (1, [2]) is worth 0.5 and the 2-gram (1, [1, 2]) is worth 0.
+    assert_eq!(nst.move_value(&1, &[1], &2), 0.25);
+    assert_eq!(nst.move_value(&0, &[2], &3), 1.);
+    assert_eq!(nst.move_value(&0, &[], &4), 1.);
+}
+
+#[test]
+fn test_cutoff_playout_backpropagates_the_heuristic() {
+    use crate::test_games::Race;
+    use crate::HeuristicMcts;
+
+    let args = CutoffArgs { depth: 3, decisive_margin: None };
+    let (state, played) = Playout::<Race, ()>::playout(&mut CutoffPlayout, Race(0), args);
+    assert_eq!(played.len(), 3);
+    assert!(!state.is_final());
+
+    let game = Race(0);
+    let mut mcts = HeuristicMcts::new(&game);
+    mcts.execute(&1., args);
+    // The expanded move and the 3 moves of the playout can't finish the race.
+    let root = mcts.tree().root().value();
+    assert_eq!((root.sum_rewards.get(&0), root.sum_rewards.get(&1)), (0.75, 0.25));
+}
+
+#[test]
+fn test_greedy_playouts_play_the_best_scored_move() {
+    use crate::test_games::Race;
+
+    struct Largest;
+
+    impl MoveScorer<Race> for Largest {
+        fn score(_state: &Race, m: &u8) -> f64 {
+            *m as f64
+        }
+    }
+
+    let (_, played) = Playout::<Race, ()>::playout(
+        &mut EpsilonGreedyPlayout::<Largest>::default(),
+        Race(0),
+        0.,
+    );
+    assert!(played.iter().all(|(_, m)| *m == 3));
+    let (_, played) =
+        Playout::<Race, ()>::playout(&mut SoftmaxPlayout::<Largest>::default(), Race(0), 0.01);
+    assert!(played.iter().all(|(_, m)| *m == 3));
+}
+
+#[test]
+fn test_mast_plays_the_moves_with_the_best_average() {
+    use crate::test_games::Race;
+
+    let mut mast = MastPlayout::<Race>::new(0.);
+    Playout::backprop(&mut mast, &[], &[(0, 3), (1, 1)], &RewardVec::single(0, 1.));
+    Playout::backprop(&mut mast, &[], &[(0, 1), (0, 3)], &RewardVec::uniform(0.5));
+    assert_eq!(mast.move_value(&0, &3), 0.75);
+    assert_eq!(mast.move_value(&0, &1), 0.5);
+    assert_eq!(mast.move_value(&1, &1), 0.);
+    assert_eq!(mast.move_value(&0, &2), 0.);
+    let (_, played) = mast.playout(Race(0), 0.);
+    assert_eq!(played[0], (0, 3));
+}
+
+#[test]
+fn test_ppa_weights_move_toward_the_winning_sequence() {
+    use crate::test_games::Race;
+
+    let mut ppa = PpaPlayout::<Race>::new(0.1);
+    // Player 0 played 3 and then won, after player 1 played 1.
+    ppa.last_playout = vec![(0, vec![1, 2, 3], 2), (1, vec![1, 2, 3], 0)];
+    let moves = [(0, 3), (1, 1)];
+    Playout::backprop(&mut ppa, &[], &moves, &RewardVec::single(0, 1.));
+    assert!(ppa.weight(&0, &3) > 0.);
+    assert!(ppa.weight(&0, &1) < 0. && ppa.weight(&0, &2) < 0.);
+    assert!(ppa.weights().keys().all(|(player, _)| *player == 0));
+    let (_, played) = ppa.playout(Race(0), ());
+    assert_eq!(ppa.last_playout.len(), played.len());
+}
+
+#[test]
+fn test_lgr_learns_winning_replies_and_forgets_losing_ones() {
+    use crate::test_games::Race;
+
+    let mut lgr = LastGoodReplyPlayout::<Race>::new(true);
+    Playout::backprop(&mut lgr, &[2], &[(1, 3), (0, 1)], &RewardVec::single(1, 1.));
+    assert_eq!(lgr.replies().len(), 1);
+    assert_eq!(lgr.reply(&1, &2), Some(&3));
+    // The first move of the playout replies to the last move of the historic.
+    let (_, played) = lgr.playout_from(&[2], Race(1), ());
+    assert_eq!(played[0], (1, 3));
+
+    Playout::backprop(&mut lgr, &[2], &[(1, 3), (0, 2)], &RewardVec::single(0, 1.));
+    assert_eq!(lgr.reply(&1, &2), None);
+    assert_eq!(lgr.reply(&0, &3), Some(&2));
+}
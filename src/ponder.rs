@@ -0,0 +1,198 @@
+use std::fmt::Display;
+use std::thread::{self, JoinHandle};
+
+use num_traits::Zero;
+
+use crate::stopping::CancellationToken;
+use crate::traits::{BackPropPolicy, GameTrait, LazyTreePolicy, Playout};
+use crate::tree_search::LazyMcts;
+use crate::Evaluator;
+
+/// A search owning its root state.
+type OwnedMcts<State, TP, PP, BP, EV, A, R> = LazyMcts<'static, State, TP, PP, BP, EV, A, R>;
+/// The background thread, returning the search when it stops.
+type PonderHandle<State, TP, PP, BP, EV, A, R> = JoinHandle<OwnedMcts<State, TP, PP, BP, EV, A, R>>;
+
+/// Runs an owned search in a background thread, for example to keep searching while the
+/// opponent thinks. When a move is played the search advances to it, keeping the explored
+/// subtree if any, and goes on.
+pub struct Ponderer<State, TP, PP, BP, EV, AddInfo, Reward>
+    where
+        State: GameTrait + 'static,
+        TP: LazyTreePolicy<State, EV, AddInfo, Reward>,
+        PP: Playout<State, EV::EvalResult>,
+        BP: BackPropPolicy<Option<State::Move>, State::Move, Reward, AddInfo, EV::EvalResult>,
+        EV: Evaluator<State, Reward, AddInfo>,
+        AddInfo: Clone + Default,
+        Reward: Clone,
+{
+    mcts: Option<OwnedMcts<State, TP, PP, BP, EV, AddInfo, Reward>>,
+    handle: Option<PonderHandle<State, TP, PP, BP, EV, AddInfo, Reward>>,
+    token: CancellationToken,
+    evaluator_args: EV::Args,
+    playout_args: PP::Args,
+}
+
+impl<State, TP, PP, BP, EV, AddInfo, Reward> Ponderer<State, TP, PP, BP, EV, AddInfo, Reward>
+    where
+        State: GameTrait + 'static,
+        TP: LazyTreePolicy<State, EV, AddInfo, Reward>,
+        PP: Playout<State, EV::EvalResult>,
+        BP: BackPropPolicy<Option<State::Move>, State::Move, Reward, AddInfo, EV::EvalResult>,
+        EV: Evaluator<State, Reward, AddInfo>,
+        AddInfo: Clone + Default,
+        Reward: Clone + Zero + Display,
+        OwnedMcts<State, TP, PP, BP, EV, AddInfo, Reward>: Send + 'static,
+        EV::Args: Clone + Send + 'static,
+        PP::Args: Clone + Send + 'static,
+{
+    /// Wraps the search, it doesn't start before `start` is called.
+    pub fn new(
+        mcts: OwnedMcts<State, TP, PP, BP, EV, AddInfo, Reward>,
+        evaluator_args: EV::Args,
+        playout_args: PP::Args,
+    ) -> Self {
+        Self {
+            mcts: Some(mcts),
+            handle: None,
+            token: CancellationToken::new(),
+            evaluator_args,
+            playout_args,
+        }
+    }
+
+    /// Starts searching in a background thread until the search is stopped. Does nothing if the
+    /// search is running or if the root is final.
+    pub fn start(&mut self) {
+        let mut mcts = match self.mcts.take() {
+            Some(mcts) if !mcts.root_state().is_final() => mcts,
+            mcts => {
+                self.mcts = mcts;
+                return;
+            }
+        };
+        self.token.reset();
+        let token = self.token.clone();
+        let evaluator_args = self.evaluator_args.clone();
+        let playout_args = self.playout_args.clone();
+        self.handle = Some(thread::spawn(move || {
+            while !token.is_cancelled() {
+                mcts.execute(&evaluator_args, playout_args.clone());
+            }
+            mcts
+        }));
+    }
+
+    /// Asks the search to stop after the current iteration, without waiting for it.
+    pub fn cancel(&self) {
+        self.token.cancel();
+    }
+
+    /// The token stopping the search, to cancel it from another thread.
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.token.clone()
+    }
+
+    /// Stops the search and waits for the background thread.
+    pub fn stop(&mut self) {
+        self.cancel();
+        if let Some(handle) = self.handle.take() {
+            self.mcts = Some(handle.join().expect("the pondering thread panicked"));
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.handle.is_some()
+    }
+
+    /// Plays `m` at the root, see `LazyMcts::advance`, then resumes the search if it was running.
+    /// Returns true if the subtree of `m` was kept.
+    pub fn advance(&mut self, m: &State::Move) -> bool
+        where
+            State::Move: PartialEq,
+    {
+        let running = self.is_running();
+        self.stop();
+        let kept = self.mcts.as_mut().unwrap().advance(m);
+        if running {
+            self.start();
+        }
+        kept
+    }
+
+    /// Stops the search and returns the best move found, `None` if no move was explored yet, for
+    /// example right after advancing to a move missing from the tree.
+    pub fn best_move(&mut self) -> Option<State::Move> {
+        self.stop();
+        let mcts = self.mcts.as_ref().unwrap();
+        if mcts.tree().root().has_children() {
+            Some(mcts.best_move(&self.evaluator_args))
+        } else {
+            None
+        }
+    }
+
+    /// The search, only available while it is stopped.
+    pub fn mcts(&self) -> Option<&OwnedMcts<State, TP, PP, BP, EV, AddInfo, Reward>> {
+        self.mcts.as_ref()
+    }
+
+    /// Stops the search and returns it.
+    pub fn into_inner(mut self) -> OwnedMcts<State, TP, PP, BP, EV, AddInfo, Reward> {
+        self.stop();
+        self.mcts.take().unwrap()
+    }
+}
+
+impl<State, TP, PP, BP, EV, AddInfo, Reward> Drop
+    for Ponderer<State, TP, PP, BP, EV, AddInfo, Reward>
+    where
+        State: GameTrait + 'static,
+        TP: LazyTreePolicy<State, EV, AddInfo, Reward>,
+        PP: Playout<State, EV::EvalResult>,
+        BP: BackPropPolicy<Option<State::Move>, State::Move, Reward, AddInfo, EV::EvalResult>,
+        EV: Evaluator<State, Reward, AddInfo>,
+        AddInfo: Clone + Default,
+        Reward: Clone,
+{
+    fn drop(&mut self) {
+        self.token.cancel();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+#[test]
+fn test_ponderer_keeps_the_subtree_of_the_played_move() {
+    use crate::test_games::Race;
+    use crate::DefaultMcts;
+    use std::time::Duration;
+
+    let mut ponderer = Ponderer::new(DefaultMcts::new_owned(Race(0)), 1., ());
+    ponderer.start();
+    thread::sleep(Duration::from_millis(50));
+    assert!(ponderer.is_running());
+    assert!(ponderer.advance(&2));
+    assert!(ponderer.is_running());
+    thread::sleep(Duration::from_millis(50));
+    ponderer.stop();
+    let mcts = ponderer.mcts().unwrap();
+    assert_eq!(mcts.root_state().0, 2);
+    assert!(mcts.tree().root().value().n_visits > 0);
+    assert!(mcts.tree().root().children().all(|c| c.value().incoming_move().is_some()));
+}
+
+#[test]
+fn test_ponderer_has_no_best_move_before_searching() {
+    use crate::test_games::Race;
+    use crate::DefaultMcts;
+    use std::time::Duration;
+
+    let mut ponderer = Ponderer::new(DefaultMcts::new_owned(Race(0)), 1., ());
+    assert!(!ponderer.advance(&2));
+    assert_eq!(ponderer.best_move(), None);
+    ponderer.start();
+    thread::sleep(Duration::from_millis(20));
+    assert!(ponderer.best_move().is_some());
+}
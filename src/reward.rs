@@ -0,0 +1,258 @@
+use std::fmt::{Debug, Display, Error, Formatter};
+use std::ops::{Add, AddAssign, Mul};
+
+use num_traits::Zero;
+
+use crate::traits::Outcome;
+
+/// A reward which can be read from the point of view of a player, used by the root strategies
+/// comparing the children of the root.
+pub trait PlayerReward<Player> {
+    fn reward_of(&self, player: &Player) -> f64;
+}
+
+impl<Player> PlayerReward<Player> for f64 {
+    fn reward_of(&self, _player: &Player) -> f64 {
+        *self
+    }
+}
+
+impl<Player: Clone + Eq> PlayerReward<Player> for RewardVec<Player> {
+    fn reward_of(&self, player: &Player) -> f64 {
+        self.get(player)
+    }
+}
+
+/// Rewards of one or several simulations for each player. Storing a reward per player allows
+/// every node to be evaluated from the point of view of the player choosing it (max^n), so it
+/// works for two players adversarial games as well as for games with 3 or more players.
+/// The reward of a player is the reward shared by every player plus its own entry if any.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct RewardVec<Player> {
+    shared: f64,
+    rewards: Vec<(Player, f64)>,
+}
+
+impl<Player: Clone + Eq> RewardVec<Player> {
+    pub fn new() -> Self {
+        Self::uniform(0.)
+    }
+
+    /// Creates a reward vector where only `player` is rewarded.
+    pub fn single(player: Player, reward: f64) -> Self {
+        Self {
+            shared: 0.,
+            rewards: vec![(player, reward)],
+        }
+    }
+
+    /// Creates a reward vector where every player has the same reward.
+    pub fn uniform(reward: f64) -> Self {
+        Self {
+            shared: reward,
+            rewards: vec![],
+        }
+    }
+
+    /// Maps the outcome of a game to rewards in [0, 1]: 1 for a win, 0.5 for a draw and 0 for a
+    /// loss. Scores are ranked the same way unless `score_bounds` is given, in that case the
+    /// margin of victory of each player against its best opponent is normalized to [0, 1]. Bounds
+    /// with an empty range are ignored, every score is then the same and the game is a draw.
+    pub fn from_outcome(outcome: &Outcome<Player>, score_bounds: Option<(f64, f64)>) -> Self {
+        let score_bounds = score_bounds.filter(|(min, max)| max > min);
+        match outcome {
+            Outcome::Win(player) => Self::single(player.clone(), 1.),
+            Outcome::Draw => Self::uniform(0.5),
+            Outcome::Scores(scores) => {
+                let mut rewards = Self::new();
+                for (player, score) in scores {
+                    let best_opponent = scores
+                        .iter()
+                        .filter(|(p, _)| p != player)
+                        .map(|(_, s)| *s)
+                        .fold(None, |acc: Option<f64>, s| Some(acc.map_or(s, |a| a.max(s))));
+                    let reward = match (score_bounds, best_opponent) {
+                        (Some((min, max)), None) => (score - min) / (max - min),
+                        (Some((min, max)), Some(opponent)) => {
+                            0.5 + (score - opponent) / (2. * (max - min))
+                        }
+                        (None, None) => 1.,
+                        (None, Some(opponent)) if *score > opponent => 1.,
+                        (None, Some(opponent)) if *score == opponent => 0.5,
+                        (None, Some(_)) => 0.,
+                    };
+                    rewards.add_reward(player.clone(), reward);
+                }
+                rewards
+            }
+        }
+    }
+
+    /// Returns the reward of the player.
+    pub fn get(&self, player: &Player) -> f64 {
+        self.shared
+            + self
+                .rewards
+                .iter()
+                .find(|(p, _)| p == player)
+                .map_or(0., |(_, r)| *r)
+    }
+
+    /// Adds `reward` to the reward of the player.
+    pub fn add_reward(&mut self, player: Player, reward: f64) {
+        match self.rewards.iter_mut().find(|(p, _)| *p == player) {
+            Some((_, r)) => *r += reward,
+            None => self.rewards.push((player, reward)),
+        }
+    }
+
+    /// Iterates over the players having an entry and their rewards, without the shared reward.
+    pub fn iter(&self) -> impl Iterator<Item = &(Player, f64)> {
+        self.rewards.iter()
+    }
+}
+
+impl<Player: Clone + Eq> Default for RewardVec<Player> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Player: Clone + Eq> AddAssign for RewardVec<Player> {
+    fn add_assign(&mut self, rhs: Self) {
+        self.shared += rhs.shared;
+        for (player, reward) in rhs.rewards {
+            self.add_reward(player, reward)
+        }
+    }
+}
+
+impl<Player: Clone + Eq> Add for RewardVec<Player> {
+    type Output = Self;
+
+    fn add(mut self, rhs: Self) -> Self::Output {
+        self += rhs;
+        self
+    }
+}
+
+/// Multiplies the rewards of each player.
+impl<Player: Clone + Eq> Mul for RewardVec<Player> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        let mut product = Self::uniform(self.shared * rhs.shared);
+        for (player, _) in self.rewards.iter().chain(rhs.rewards.iter()) {
+            if !product.rewards.iter().any(|(p, _)| p == player) {
+                let reward = self.get(player) * rhs.get(player) - product.shared;
+                product.rewards.push((player.clone(), reward));
+            }
+        }
+        product
+    }
+}
+
+impl<Player: Clone + Eq> Zero for RewardVec<Player> {
+    fn zero() -> Self {
+        Self::new()
+    }
+
+    fn is_zero(&self) -> bool {
+        self.shared.is_zero() && self.rewards.iter().all(|(_, r)| r.is_zero())
+    }
+}
+
+impl<Player: Debug> Display for RewardVec<Player> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(f, "[*: {}", self.shared)?;
+        for (player, reward) in &self.rewards {
+            write!(f, ", {:?}: {}", player, reward)?;
+        }
+        f.write_str("]")
+    }
+}
+
+#[test]
+fn test_reward_vec_sum() {
+    let sum = RewardVec::single(1, 1.) + RewardVec::single(2, 1.) + RewardVec::single(1, 1.);
+    assert_eq!(sum.get(&1), 2.);
+    assert_eq!(sum.get(&2), 1.);
+    assert_eq!(sum.get(&3), 0.);
+    let squared = (sum.clone() + RewardVec::uniform(1.)) * (sum + RewardVec::uniform(1.));
+    assert_eq!((squared.get(&1), squared.get(&2), squared.get(&3)), (9., 4., 1.));
+}
+
+#[test]
+fn test_reward_vec_from_outcome() {
+    let draw = RewardVec::<u8>::from_outcome(&Outcome::Draw, None);
+    assert_eq!(draw.get(&1), 0.5);
+    let scores = Outcome::Scores(vec![(1, 10.), (2, 4.)]);
+    let ranked = RewardVec::from_outcome(&scores, None);
+    assert_eq!((ranked.get(&1), ranked.get(&2)), (1., 0.));
+    let margins = RewardVec::from_outcome(&scores, Some((0., 20.)));
+    assert_eq!((margins.get(&1), margins.get(&2)), (0.65, 0.35));
+    let tie = Outcome::Scores(vec![(1, 3.), (2, 3.)]);
+    let empty_range = RewardVec::from_outcome(&tie, Some((3., 3.)));
+    assert_eq!((empty_range.get(&1), empty_range.get(&2)), (0.5, 0.5));
+}
+
+#[test]
+fn test_selection_maximizes_the_reward_of_the_player_to_move() {
+    use crate::traits::GameTrait;
+    use crate::DefaultMcts;
+
+    /// Three players, player 0 then player 1 choose 0 or 1. When player 0 chooses 0, player 1
+    /// wins by choosing 0, else player 0 wins. When player 0 chooses 1, player 1 draws by choosing
+    /// 0, else player 2 wins.
+    #[derive(Clone)]
+    struct ThreePlayers(Vec<u8>);
+
+    impl GameTrait for ThreePlayers {
+        type Player = u8;
+        type Move = u8;
+
+        fn legals_moves(&self) -> Vec<u8> {
+            vec![0, 1]
+        }
+
+        fn player_turn(&self) -> u8 {
+            self.0.len() as u8
+        }
+
+        fn hash(&self) -> u64 {
+            0
+        }
+
+        fn is_final(&self) -> bool {
+            self.0.len() == 2
+        }
+
+        fn do_move(&mut self, m: &u8) {
+            self.0.push(*m)
+        }
+
+        fn outcome(&self) -> Outcome<u8> {
+            match self.0[..] {
+                [0, 0] => Outcome::Win(1),
+                [0, _] => Outcome::Win(0),
+                [_, 0] => Outcome::Draw,
+                _ => Outcome::Win(2),
+            }
+        }
+    }
+
+    let game = ThreePlayers(vec![]);
+    let mut mcts = DefaultMcts::new(&game);
+    for _ in 0..2000 {
+        mcts.execute(&1., ());
+    }
+    // Player 1 answers each move of player 0 with its own best move, so player 0 only gets a draw
+    // by choosing 1, a paranoid search would see both moves as lost.
+    let root = mcts.tree().root();
+    for child in root.children() {
+        let answer = child.children().max_by_key(|c| c.value().n_visits).unwrap();
+        assert_eq!(answer.value().incoming_move(), Some(&0));
+    }
+    assert_eq!(mcts.best_move(&1.), 1);
+}
@@ -0,0 +1,53 @@
+use std::cell::RefCell;
+use std::mem::swap;
+
+use rand::rngs::StdRng;
+use rand::{Error, RngCore, SeedableRng};
+
+thread_local! {
+    static RNG: RefCell<StdRng> = RefCell::new(StdRng::from_entropy());
+}
+
+/// A handle to the random number generator of the current thread. It is the generator of the
+/// search running on the thread if it was seeded, see `LazyMcts::seed`, else a generator seeded
+/// from entropy.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct SearchRng;
+
+pub(crate) fn search_rng() -> SearchRng {
+    SearchRng
+}
+
+/// Runs `f` with `rng` as the random number generator of the current thread, the generator of the
+/// thread is put back afterwards, even if `f` panics.
+pub(crate) fn with_rng<T>(rng: &mut StdRng, f: impl FnOnce() -> T) -> T {
+    struct Restore<'a>(&'a mut StdRng);
+
+    impl Drop for Restore<'_> {
+        fn drop(&mut self) {
+            RNG.with(|thread_rng| swap(&mut *thread_rng.borrow_mut(), self.0));
+        }
+    }
+
+    RNG.with(|thread_rng| swap(&mut *thread_rng.borrow_mut(), rng));
+    let _restore = Restore(rng);
+    f()
+}
+
+impl RngCore for SearchRng {
+    fn next_u32(&mut self) -> u32 {
+        RNG.with(|rng| rng.borrow_mut().next_u32())
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        RNG.with(|rng| rng.borrow_mut().next_u64())
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        RNG.with(|rng| rng.borrow_mut().fill_bytes(dest))
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        RNG.with(|rng| rng.borrow_mut().try_fill_bytes(dest))
+    }
+}
@@ -0,0 +1,269 @@
+use std::marker::PhantomData;
+use std::ops::{AddAssign, Mul};
+
+use ego_tree::{NodeId, NodeRef, Tree};
+use num_traits::Zero;
+
+use crate::aliases::{LazyMctsNode, LazyMctsTree};
+use crate::defaults::{DefaultBackProp, DefaultLazyTreePolicy};
+use crate::mcts_node::{move_path, MctsNode};
+use crate::reward::RewardVec;
+use crate::traits::{BackPropPolicy, Evaluator, GameTrait, LazyTreePolicy};
+
+/// Pessimistic and optimistic bounds on the final reward of the root player, stored in the
+/// additional info of each node. The opponents are assumed to minimize this reward, so for two
+/// players games it's exactly the alpha-beta bounds of the game.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScoreBounds {
+    pub pessimistic: f64,
+    pub optimistic: f64,
+    /// True if the root player is to move at this node.
+    pub max_node: bool,
+}
+
+impl ScoreBounds {
+    /// The exact reward of the node is known.
+    #[inline]
+    pub fn is_solved(&self) -> bool {
+        self.pessimistic >= self.optimistic
+    }
+}
+
+impl Default for ScoreBounds {
+    fn default() -> Self {
+        Self {
+            pessimistic: 0.,
+            optimistic: 1.,
+            max_node: true,
+        }
+    }
+}
+
+/// Score bounded tree policy, children whose optimistic bound can't beat the pessimistic bound of
+/// their parent (or the reverse when an opponent is choosing) are pruned from the selection.
+/// Final states are solved when they are expanded.
+pub struct ScoreBoundedTreePolicy<State: GameTrait, EV: Evaluator<State, Reward, ScoreBounds>,
+    Reward: Clone> {
+    phantom_state: PhantomData<State>,
+    phantom_ev: PhantomData<EV>,
+    phamtom_r: PhantomData<Reward>,
+}
+
+impl<State, EV, Reward> ScoreBoundedTreePolicy<State, EV, Reward>
+    where
+        State: GameTrait,
+        Reward: Clone + Zero,
+        EV: Evaluator<State, Reward, ScoreBounds, Args=f64, EvalResult=RewardVec<State::Player>>,
+{
+    /// Same as the default selection, but using the pruned best child.
+    pub fn select(
+        tree: &LazyMctsTree<State, Reward, ScoreBounds>,
+        start: NodeId,
+        mut state: State,
+        evaluator_args: &EV::Args,
+    ) -> (NodeId, State) {
+        let mut current_node_id = start;
+        while tree.get(current_node_id).unwrap().has_children()
+            && !tree.get(current_node_id).unwrap().value().can_add_child() {
+            current_node_id =
+                Self::best_child(tree, &state.player_turn(), current_node_id, evaluator_args);
+            let m = tree
+                .get(current_node_id)
+                .unwrap()
+                .value()
+                .incoming_move()
+                .expect("A child always has an incoming move");
+            state.do_move(m);
+        }
+        (current_node_id, state)
+    }
+
+    fn is_pruned(
+        parent: &ScoreBounds,
+        child: &NodeRef<LazyMctsNode<State, Reward, ScoreBounds>>,
+    ) -> bool {
+        let child = &child.value().additional_info;
+        if parent.max_node {
+            child.optimistic <= parent.pessimistic
+        } else {
+            child.pessimistic >= parent.optimistic
+        }
+    }
+}
+
+impl<State, EV, Reward> LazyTreePolicy<State, EV, ScoreBounds, Reward>
+for ScoreBoundedTreePolicy<State, EV, Reward>
+    where
+        State: GameTrait,
+        Reward: Clone + Zero,
+        EV: Evaluator<State, Reward, ScoreBounds, Args=f64, EvalResult=RewardVec<State::Player>>,
+{
+    fn tree_policy_from(
+        tree: &mut LazyMctsTree<State, Reward, ScoreBounds>,
+        start: NodeId,
+        root_state: State,
+        evaluator_args: &EV::Args,
+    ) -> (NodeId, State) {
+        let root_player = root_state.player_turn();
+        let state = Self::update_state(root_state, &move_path(tree, start));
+        let (selected_node_id, state) = Self::select(tree, start, state, evaluator_args);
+        let node = tree.get_mut(selected_node_id).unwrap();
+        let (new_node_id, new_state) =
+            DefaultLazyTreePolicy::<State, EV, ScoreBounds, Reward>::expand(node, state);
+        if new_node_id != selected_node_id {
+            let mut new_node = tree.get_mut(new_node_id).unwrap();
+            let new_node = new_node.value();
+            new_node.additional_info.max_node = new_state.player_turn() == root_player;
+            if new_state.is_final() {
+                let reward = EV::evaluate_leaf(&new_state, &root_player).get(&root_player);
+                new_node.additional_info.pessimistic = reward;
+                new_node.additional_info.optimistic = reward;
+            }
+        }
+        (new_node_id, new_state)
+    }
+
+    fn best_child(
+        tree: &LazyMctsTree<State, Reward, ScoreBounds>,
+        turn: &State::Player,
+        parent_id: NodeId,
+        eval_args: &EV::Args,
+    ) -> NodeId {
+        let parent_node = tree.get(parent_id).unwrap();
+        let parent_bounds = &parent_node.value().additional_info;
+        let n_visits = parent_node.value().n_visits;
+        let best_unpruned = parent_node
+            .children()
+            .filter(|child| !Self::is_pruned(parent_bounds, child))
+            .max_by_key(|child| EV::eval_child(child.value(), turn, n_visits, eval_args));
+        match best_unpruned {
+            Some(child) => child.id(),
+            // Every child is pruned when the node is solved, so we choose the best proven child.
+            None if parent_bounds.max_node => parent_node
+                .children()
+                .max_by(|a, b| {
+                    let (a, b) = (&a.value().additional_info, &b.value().additional_info);
+                    a.pessimistic.partial_cmp(&b.pessimistic).unwrap()
+                })
+                .unwrap()
+                .id(),
+            None => parent_node
+                .children()
+                .min_by(|a, b| {
+                    let (a, b) = (&a.value().additional_info, &b.value().additional_info);
+                    a.optimistic.partial_cmp(&b.optimistic).unwrap()
+                })
+                .unwrap()
+                .id(),
+        }
+    }
+}
+
+/// Backpropagates the reward like the default backprop, then propagates the score bounds from the
+/// leaf to the root like alpha-beta bounds.
+pub struct ScoreBoundedBackProp;
+
+impl ScoreBoundedBackProp {
+    /// Recomputes the bounds of a node from its children, returns false if they didn't change.
+    fn update_bounds<T: Clone, Move: Clone, R: Clone>(
+        tree: &mut Tree<MctsNode<T, Move, R, ScoreBounds>>,
+        node_id: NodeId,
+    ) -> bool {
+        let node = tree.get(node_id).unwrap();
+        if !node.has_children() {
+            return false;
+        }
+        let fully_expanded = !node.value().can_add_child();
+        let old = node.value().additional_info.clone();
+        let children = node.children().map(|c| &c.value().additional_info);
+        let (pessimistic, optimistic) = if old.max_node {
+            let (pess, opt) = children.fold((0f64, 0f64), |(pess, opt), c| {
+                (pess.max(c.pessimistic), opt.max(c.optimistic))
+            });
+            (pess, if fully_expanded { opt } else { old.optimistic })
+        } else {
+            let (pess, opt) = children.fold((1f64, 1f64), |(pess, opt), c| {
+                (pess.min(c.pessimistic), opt.min(c.optimistic))
+            });
+            (if fully_expanded { pess } else { old.pessimistic }, opt)
+        };
+        let pessimistic = pessimistic.max(old.pessimistic);
+        let optimistic = optimistic.min(old.optimistic);
+        if pessimistic == old.pessimistic && optimistic == old.optimistic {
+            return false;
+        }
+        let mut node = tree.get_mut(node_id).unwrap();
+        node.value().additional_info.pessimistic = pessimistic;
+        node.value().additional_info.optimistic = optimistic;
+        true
+    }
+}
+
+impl<T: Clone, Move: Clone, R: AddAssign + Mul<Output=R> + Clone + Zero>
+BackPropPolicy<T, Move, R, ScoreBounds> for ScoreBoundedBackProp
+{
+    fn backprop(tree: &mut Tree<MctsNode<T, Move, R, ScoreBounds>>, leaf: NodeId, reward: R) {
+        DefaultBackProp::backprop(tree, leaf, reward);
+        let mut current_node_id = tree.get(leaf).unwrap().parent().map(|p| p.id());
+        while let Some(node_id) = current_node_id {
+            if !Self::update_bounds(tree, node_id) {
+                break;
+            }
+            current_node_id = tree.get(node_id).unwrap().parent().map(|p| p.id());
+        }
+    }
+}
+
+#[test]
+fn test_score_bounded_solves_nim() {
+    use crate::traits::Outcome;
+    use crate::ScoreBoundedMcts;
+
+    /// Each player takes 1 or 2 sticks, the one who takes the last stick wins.
+    #[derive(Clone)]
+    struct Nim {
+        sticks: u8,
+        turn: u8,
+    }
+
+    impl GameTrait for Nim {
+        type Player = u8;
+        type Move = u8;
+
+        fn legals_moves(&self) -> Vec<u8> {
+            (1..=self.sticks.min(2)).collect()
+        }
+
+        fn player_turn(&self) -> u8 {
+            self.turn
+        }
+
+        fn hash(&self) -> u64 {
+            0
+        }
+
+        fn is_final(&self) -> bool {
+            self.sticks == 0
+        }
+
+        fn do_move(&mut self, m: &u8) {
+            self.sticks -= m;
+            self.turn = 1 - self.turn;
+        }
+
+        fn outcome(&self) -> Outcome<u8> {
+            Outcome::Win(1 - self.turn)
+        }
+    }
+
+    let nim = Nim { sticks: 4, turn: 0 };
+    let mut mcts = ScoreBoundedMcts::new(&nim);
+    for _ in 0..200 {
+        mcts.execute(&1., ());
+    }
+    let root_bounds = &mcts.tree().root().value().additional_info;
+    assert!(root_bounds.is_solved());
+    assert_eq!(root_bounds.pessimistic, 1.);
+    assert_eq!(mcts.best_move(&1.), 1);
+}
@@ -0,0 +1,518 @@
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::time::Instant;
+
+use ego_tree::NodeId;
+use num_traits::Zero;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::aliases::{LazyMctsNode, LazyMctsTree, Nat, PlayedMoves};
+use crate::defaults::root_node;
+use crate::mcts_node::move_path;
+use crate::memory::{MemoryLimit, MemoryPolicy, MemoryUsage, node_bytes};
+use crate::reward::PlayerReward;
+use crate::rng::with_rng;
+use crate::stats::SearchStats;
+use crate::stopping::{CancellationToken, SearchReport, StoppingRule};
+use crate::traits::{GameTrait, SearchObserver, UndoableGame};
+use crate::tree_search::FinalMove;
+
+/// The policies given to the iterations of a `SearchCore`: the static policies of a `LazyMcts`
+/// with their arguments, or the boxed policies of a `DynMcts`.
+pub(crate) trait SearchPolicies<State: GameTrait, R: Clone, A: Clone + Default> {
+    type EvalResult;
+
+    /// Selects a node from `start` and expands it, see `LazyTreePolicy::tree_policy_from`.
+    fn tree_policy_from(
+        &mut self,
+        tree: &mut LazyMctsTree<State, R, A>,
+        start: NodeId,
+        root_state: State,
+    ) -> (NodeId, State);
+
+    /// Selects a leaf from `start` without expanding it, see `LazyTreePolicy::select_leaf`.
+    fn select_leaf(
+        &mut self,
+        tree: &LazyMctsTree<State, R, A>,
+        start: NodeId,
+        root_state: State,
+    ) -> (NodeId, State);
+
+    fn playout(&mut self, historic: &[State::Move], state: State) -> (State, PlayedMoves<State>);
+
+    fn evaluate_leaf(&self, state: &State, turn: &State::Player) -> Self::EvalResult;
+
+    /// Gives the playout and its evaluation back to the playout policy, see `Playout::backprop`.
+    fn learn(
+        &mut self,
+        historic: &[State::Move],
+        moves: &[(State::Player, State::Move)],
+        eval: &Self::EvalResult,
+    );
+
+    fn backprop(
+        &mut self,
+        tree: &mut LazyMctsTree<State, R, A>,
+        leaf: NodeId,
+        eval: Self::EvalResult,
+    );
+
+    fn best_child(
+        &self,
+        tree: &LazyMctsTree<State, R, A>,
+        turn: &State::Player,
+        parent_id: NodeId,
+    ) -> NodeId;
+}
+
+/// The tree of a lazy search with everything its iterations keep track of, shared by the
+/// `LazyMcts` and the `DynMcts` which only differ by their policies.
+#[derive(Clone)]
+pub(crate) struct SearchCore<'a, State, R, A>
+    where
+        State: GameTrait,
+        R: Clone,
+        A: Clone + Default,
+{
+    pub(crate) root_state: Cow<'a, State>,
+    pub(crate) tree: LazyMctsTree<State, R, A>,
+    /// The child of the root chosen by the last root search, if no iteration happened since.
+    pub(crate) root_choice: Option<NodeId>,
+    /// The state the moves are played on and undone by `execute_with_undo`.
+    pub(crate) working_state: Option<State>,
+    memory_limit: Option<MemoryLimit>,
+    /// The last recycling couldn't bring the tree under its target since only the children of
+    /// the root were left: the tree stops expanding until it is under its budget again.
+    recycling_exhausted: bool,
+    pub(crate) memory_usage: MemoryUsage,
+    pub(crate) cancellation_token: Option<CancellationToken>,
+    /// The number of visits of the root at the last visit of each node, only kept when
+    /// recycling the least recently visited subtrees.
+    last_visits: HashMap<NodeId, Nat>,
+    stats: SearchStats,
+    /// The phases of the iterations are timed, see `LazyMcts::set_timing`.
+    pub(crate) timing: bool,
+    /// The random number generator of the search if it was seeded, see `seed`.
+    rng: Option<StdRng>,
+}
+
+impl<'a, State, R, A> SearchCore<'a, State, R, A>
+    where
+        State: GameTrait,
+        R: Clone + Zero,
+        A: Clone + Default,
+{
+    pub(crate) fn new(root_state: Cow<'a, State>, capacity: usize) -> Self {
+        let tree =
+            LazyMctsTree::<State, R, A>::with_capacity(root_node(&*root_state), capacity);
+        let memory_usage = MemoryUsage {
+            nodes: 1,
+            bytes: node_bytes::<State, R, A>(tree.root().value()),
+            pruned_nodes: 0,
+        };
+        Self {
+            root_state,
+            tree,
+            root_choice: None,
+            working_state: None,
+            memory_limit: None,
+            recycling_exhausted: false,
+            memory_usage,
+            cancellation_token: None,
+            last_visits: HashMap::new(),
+            stats: SearchStats::default(),
+            timing: false,
+            rng: None,
+        }
+    }
+
+    /// Executes one iteration from the root, observed by `observer`.
+    pub(crate) fn execute_observed<P, O>(&mut self, policies: &mut P, observer: &mut O)
+        where
+            P: SearchPolicies<State, R, A>,
+            O: SearchObserver<State, P::EvalResult>,
+    {
+        self.root_choice = None;
+        let root_id = self.tree.root().id();
+        let root_state = self.root_state.clone().into_owned();
+        self.execute_from(policies, root_id, root_state, observer);
+    }
+
+    /// Executes one iteration from the root on the working state, then undoes its moves.
+    pub(crate) fn execute_with_undo<P: SearchPolicies<State, R, A>>(&mut self, policies: &mut P)
+        where
+            State: UndoableGame,
+    {
+        self.root_choice = None;
+        let root_id = self.tree.root().id();
+        let state = match self.working_state.take() {
+            Some(state) => state,
+            None => self.root_state.clone().into_owned(),
+        };
+        let (mut state, historic, moves) = self.execute_from(policies, root_id, state, &mut ());
+        for (_, m) in moves.iter().rev() {
+            state.undo_move(m);
+        }
+        for m in historic.iter().rev() {
+            state.undo_move(m);
+        }
+        self.working_state = Some(state);
+    }
+
+    /// Executes one iteration whose selection starts from the node `start`, `root_state` must be
+    /// the state of the root. Returns the final state of the playout, the moves from the root to
+    /// the node the playout started from, and the moves of the playout.
+    pub(crate) fn execute_from<P, O>(
+        &mut self,
+        policies: &mut P,
+        start: NodeId,
+        root_state: State,
+        observer: &mut O,
+    ) -> (State, Vec<State::Move>, PlayedMoves<State>)
+        where
+            P: SearchPolicies<State, R, A>,
+            O: SearchObserver<State, P::EvalResult>,
+    {
+        self.with_own_rng(|core| core.iteration_from(policies, start, root_state, observer))
+    }
+
+    /// Runs `f` with the random number generator of the search if it was seeded.
+    pub(crate) fn with_own_rng<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> T {
+        match self.rng.take() {
+            Some(mut rng) => {
+                let result = with_rng(&mut rng, || f(self));
+                self.rng = Some(rng);
+                result
+            }
+            None => f(self),
+        }
+    }
+
+    /// See `execute_from`.
+    fn iteration_from<P, O>(
+        &mut self,
+        policies: &mut P,
+        start: NodeId,
+        root_state: State,
+        observer: &mut O,
+    ) -> (State, Vec<State::Move>, PlayedMoves<State>)
+        where
+            P: SearchPolicies<State, R, A>,
+            O: SearchObserver<State, P::EvalResult>,
+    {
+        let start_time = self.timing.then(Instant::now);
+        let reached = self.memory_limit.is_some_and(|limit| limit.is_reached(&self.memory_usage));
+        self.recycling_exhausted &= reached;
+        let stop_expanding = reached
+            && (self.recycling_exhausted
+                || self.memory_limit.is_some_and(|l| l.policy == MemoryPolicy::StopExpanding));
+        let (node_id, state) = if stop_expanding {
+            policies.select_leaf(&self.tree, start, root_state)
+        } else {
+            policies.tree_policy_from(&mut self.tree, start, root_state)
+        };
+        let node = self.tree.get(node_id).unwrap();
+        let expanded = node.value().n_visits == 0 && node.parent().is_some();
+        if expanded {
+            self.memory_usage.nodes += 1;
+            self.memory_usage.bytes += node_bytes::<State, R, A>(node.value());
+        }
+        let mut path: Vec<NodeId> = std::iter::once(node)
+            .chain(node.ancestors())
+            .map(|n| n.id())
+            .skip(expanded as usize)
+            .take_while(|&id| id != start)
+            .chain(std::iter::once(start))
+            .collect();
+        path.reverse();
+        observer.on_selection(&path);
+        if expanded {
+            observer.on_expansion(node_id);
+        }
+        let tree_policy_time = self.timing.then(Instant::now);
+        let historic = move_path(&self.tree, node_id);
+        let (final_state, moves) = policies.playout(&historic, state);
+        observer.on_playout(&moves);
+        let eval = policies.evaluate_leaf(&final_state, &self.root_state.player_turn());
+        observer.on_evaluation(&eval);
+        let playout_time = self.timing.then(Instant::now);
+        policies.learn(&historic, &moves, &eval);
+        policies.backprop(&mut self.tree, node_id, eval);
+        observer.on_backprop(node_id);
+        let backprop_time = self.timing.then(Instant::now);
+        self.stats.add_iteration(historic.len(), moves.len(), expanded);
+        if let (Some(start), Some(tree_policy), Some(playout), Some(backprop)) =
+            (start_time, tree_policy_time, playout_time, backprop_time)
+        {
+            self.stats.tree_policy_time += tree_policy - start;
+            self.stats.playout_time += playout - tree_policy;
+            self.stats.backprop_time += backprop - playout;
+        }
+
+        if let Some(limit) = self.memory_limit {
+            if limit.policy == MemoryPolicy::RecycleLeastRecentlyVisited {
+                let time = self.tree.root().value().n_visits;
+                let node = self.tree.get(node_id).unwrap();
+                self.last_visits.insert(node_id, time);
+                self.last_visits.extend(node.ancestors().map(|a| (a.id(), time)));
+            }
+            if limit.policy != MemoryPolicy::StopExpanding
+                && !self.recycling_exhausted
+                && limit.is_reached(&self.memory_usage)
+            {
+                self.recycle(limit.policy);
+            }
+        }
+        (final_state, historic, moves)
+    }
+
+    /// Executes up to `budget` iterations from the root, see `LazyMcts::search_observed`.
+    pub(crate) fn search<P, O>(
+        &mut self,
+        policies: &mut P,
+        budget: usize,
+        stopping_rule: &StoppingRule,
+        observer: &mut O,
+    ) -> SearchReport
+        where
+            P: SearchPolicies<State, R, A>,
+            O: SearchObserver<State, P::EvalResult>,
+            R: PlayerReward<State::Player>,
+    {
+        let progress_interval = observer.progress_interval();
+        let root_player = self.root_state.player_turn();
+        let pruned_nodes = self.memory_usage.pruned_nodes;
+        let mut iterations = 0;
+        // Why the search stopped before the end of its budget, if it did.
+        let (mut cancelled, mut stopped) = (false, false);
+        while iterations < budget {
+            if self.is_cancelled() {
+                cancelled = true;
+                break;
+            }
+            if stopping_rule.should_stop(&self.tree, &root_player, budget - iterations) {
+                stopped = true;
+                break;
+            }
+            self.execute_observed(policies, observer);
+            iterations += 1;
+            let progress = progress_interval.filter(|&interval| interval != 0);
+            if progress.is_some_and(|interval| iterations % interval == 0) {
+                let best_move =
+                    self.best_move(|tree, turn, root_id| policies.best_child(tree, turn, root_id));
+                observer.on_progress(iterations, &best_move);
+            }
+        }
+        SearchReport {
+            iterations,
+            iterations_saved: if stopped { budget - iterations } else { 0 },
+            nodes_pruned: self.memory_usage.pruned_nodes - pruned_nodes,
+            cancelled,
+        }
+    }
+
+    /// The statistics of all the iterations executed.
+    pub(crate) fn stats(&self) -> SearchStats {
+        let (parents, children) = self
+            .tree
+            .root()
+            .descendants()
+            .filter(|node| node.has_children())
+            .fold((0, 0), |(parents, children), node| {
+                (parents + 1, children + node.children().count())
+            });
+        SearchStats {
+            branching_factor: children as f64 / parents.max(1) as f64,
+            ..self.stats.clone()
+        }
+    }
+
+    pub(crate) fn memory_limit(&self) -> Option<MemoryLimit> {
+        self.memory_limit
+    }
+
+    pub(crate) fn set_memory_limit(&mut self, memory_limit: Option<MemoryLimit>) {
+        self.memory_limit = memory_limit;
+        self.recycling_exhausted = false;
+    }
+
+    pub(crate) fn seed(&mut self, seed: u64) {
+        self.rng = Some(StdRng::seed_from_u64(seed));
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancellation_token.as_ref().is_some_and(CancellationToken::is_cancelled)
+    }
+
+    /// Prunes subtrees until the tree uses three quarters of its budget. The moves leading to the
+    /// pruned subtrees are unvisited again, but the statistics of their ancestors are kept since
+    /// their simulations are still valid: the visits of a parent are then an approximation, they
+    /// can exceed the sum of the visits of its children plus one.
+    ///
+    /// The subtrees are detached in place, ego-tree never frees their nodes so the tree is only
+    /// rebuilt once the detached nodes outnumber the nodes in use.
+    fn recycle(&mut self, policy: MemoryPolicy) {
+        let limit = self.memory_limit.unwrap();
+        let root_id = self.tree.root().id();
+        let mut candidates: Vec<(Nat, NodeId)> = self
+            .tree
+            .root()
+            .descendants()
+            .filter(|n| n.parent().is_some_and(|p| p.id() != root_id))
+            .map(|n| {
+                let key = match policy {
+                    MemoryPolicy::RecycleLeastRecentlyVisited => {
+                        self.last_visits.get(&n.id()).copied().unwrap_or(0)
+                    }
+                    _ => n.value().n_visits,
+                };
+                (key, n.id())
+            })
+            .collect();
+        candidates.sort_by_key(|&(key, _)| key);
+
+        let mut pruned = HashSet::new();
+        for (_, node_id) in candidates {
+            if limit.is_recycled(&self.memory_usage) {
+                break;
+            }
+            let node = self.tree.get(node_id).unwrap();
+            if node.ancestors().any(|a| pruned.contains(&a.id())) {
+                continue;
+            }
+            let (nodes, bytes) = node.descendants().fold((0, 0), |(nodes, bytes), d| {
+                (nodes + 1, bytes + node_bytes::<State, R, A>(d.value()))
+            });
+            for descendant in node.descendants() {
+                self.last_visits.remove(&descendant.id());
+            }
+            let m = node.value().incoming_move().unwrap().clone();
+            let parent_id = node.parent().unwrap().id();
+            self.tree.get_mut(parent_id).unwrap().value().unvisited_moves.push(m);
+            self.tree.get_mut(node_id).unwrap().detach();
+            pruned.insert(node_id);
+            self.memory_usage.nodes -= nodes;
+            self.memory_usage.bytes -= bytes;
+            self.memory_usage.pruned_nodes += nodes;
+        }
+        self.recycling_exhausted = !limit.is_recycled(&self.memory_usage);
+        if self.tree.nodes().count() > 2 * self.memory_usage.nodes {
+            self.compact();
+        }
+    }
+
+    /// Rebuilds the tree without the detached subtrees, ego-tree never frees their nodes.
+    fn compact(&mut self) {
+        let (tree, ids) = self.copy_subtree(self.tree.root().id());
+        self.root_choice = self.root_choice.and_then(|id| ids.get(&id).copied());
+        self.last_visits = self
+            .last_visits
+            .iter()
+            .filter_map(|(id, &time)| ids.get(id).map(|&new_id| (new_id, time)))
+            .collect();
+        self.memory_usage.bytes = tree.values().map(node_bytes::<State, R, A>).sum();
+        self.tree = tree;
+    }
+
+    /// Copies the subtree of `node_id` in a new tree, returns it with the ids of the copied nodes
+    /// in the new tree.
+    fn copy_subtree(
+        &self,
+        node_id: NodeId,
+    ) -> (LazyMctsTree<State, R, A>, HashMap<NodeId, NodeId>) {
+        let mut tree = LazyMctsTree::<State, R, A>::with_capacity(
+            self.tree.get(node_id).unwrap().value().clone(),
+            self.memory_usage.nodes,
+        );
+        let mut ids = HashMap::new();
+        ids.insert(node_id, tree.root().id());
+        let mut stack = vec![(node_id, tree.root().id())];
+        while let Some((old_id, new_id)) = stack.pop() {
+            for child in self.tree.get(old_id).unwrap().children() {
+                let new_child_id = tree.get_mut(new_id).unwrap().append(child.value().clone()).id();
+                ids.insert(child.id(), new_child_id);
+                stack.push((child.id(), new_child_id));
+            }
+        }
+        (tree, ids)
+    }
+
+    /// See `LazyMcts::advance`.
+    pub(crate) fn advance(&mut self, m: &State::Move) -> bool
+        where
+            State::Move: PartialEq,
+    {
+        let mut root_state = self.root_state.clone().into_owned();
+        root_state.do_move(m);
+        let child = self.tree.root().children().find(|c| c.value().incoming_move() == Some(m));
+        let kept = child.is_some();
+        let tree = match child {
+            Some(child) => {
+                let (mut tree, _) = self.copy_subtree(child.id());
+                tree.root_mut().value().state = None;
+                tree
+            }
+            None => LazyMctsTree::<State, R, A>::new(root_node(&root_state)),
+        };
+        self.memory_usage.nodes = tree.nodes().count();
+        self.memory_usage.bytes = tree.values().map(node_bytes::<State, R, A>).sum();
+        self.tree = tree;
+        self.root_state = Cow::Owned(root_state);
+        self.root_choice = None;
+        self.working_state = None;
+        self.last_visits.clear();
+        kept
+    }
+
+    /// Returns the child of the root chosen by `best_child`, or the move chosen by the last root
+    /// search if no iteration happened since.
+    pub(crate) fn best_move(
+        &self,
+        best_child: impl FnOnce(&LazyMctsTree<State, R, A>, &State::Player, NodeId) -> NodeId,
+    ) -> State::Move {
+        if let Some(choice) = self.root_choice {
+            return self.move_to(choice);
+        }
+        let root_id = self.tree.root().id();
+        self.move_to(best_child(&self.tree, &self.root_state.player_turn(), root_id))
+    }
+
+    /// See `LazyMcts::final_move`, `best_child` is the choice of the tree policy.
+    pub(crate) fn final_move(
+        &self,
+        criterion: FinalMove,
+        best_child: impl FnOnce(&LazyMctsTree<State, R, A>, &State::Player, NodeId) -> NodeId,
+    ) -> State::Move
+        where
+            R: PlayerReward<State::Player>,
+    {
+        if let Some(choice) = self.root_choice {
+            return self.move_to(choice);
+        }
+        let root_player = self.root_state.player_turn();
+        let mean = |child: &LazyMctsNode<State, R, A>| {
+            child.sum_rewards.reward_of(&root_player) / child.n_visits.max(1) as f64
+        };
+        let children = self.tree.root().children();
+        let best_child = match criterion {
+            FinalMove::MostVisited => children.max_by_key(|c| c.value().n_visits).map(|c| c.id()),
+            FinalMove::MaxMeanReward => children
+                .max_by(|a, b| mean(a.value()).total_cmp(&mean(b.value())))
+                .map(|c| c.id()),
+            FinalMove::TreePolicy => return self.best_move(best_child),
+        };
+        self.move_to(best_child.expect("The root has no children"))
+    }
+
+    /// The move leading from the root to one of its children.
+    pub(crate) fn move_to(&self, child: NodeId) -> State::Move {
+        self.tree
+            .get(child)
+            .unwrap()
+            .value()
+            .incoming_move()
+            .expect("The children of the root always have an incoming move")
+            .clone()
+    }
+}
@@ -0,0 +1,132 @@
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+
+use bincode::Result;
+use ego_tree::{NodeId, Tree};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+/// A serializable copy of a tree, used to save a search and to reload it later. The nodes are
+/// stored in depth first order with their number of children, the detached nodes are not saved.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TreeSnapshot<N> {
+    pub nodes: Vec<(N, usize)>,
+}
+
+/// Why a snapshot can't be turned back into a tree.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SnapshotError {
+    /// The snapshot has no root.
+    Empty,
+    /// The snapshot has more nodes than the numbers of children declare.
+    TooManyNodes,
+    /// The snapshot has fewer nodes than the numbers of children declare.
+    MissingNodes,
+    /// The hash of the root doesn't match the state the search is resumed from.
+    RootMismatch,
+}
+
+impl Display for SnapshotError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SnapshotError::Empty => "the snapshot has no root",
+            SnapshotError::TooManyNodes => "the snapshot has more nodes than declared",
+            SnapshotError::MissingNodes => "the snapshot has fewer nodes than declared",
+            SnapshotError::RootMismatch => "the root of the snapshot doesn't match the root state",
+        })
+    }
+}
+
+impl Error for SnapshotError {}
+
+impl<N: Clone> TreeSnapshot<N> {
+    pub fn from_tree(tree: &Tree<N>) -> Self {
+        let nodes = tree
+            .root()
+            .descendants()
+            .map(|node| (node.value().clone(), node.children().count()))
+            .collect();
+        Self { nodes }
+    }
+
+    /// Rebuilds the tree, fails if the snapshot is empty or if its numbers of children don't
+    /// match its number of nodes.
+    pub fn to_tree(&self) -> std::result::Result<Tree<N>, SnapshotError> {
+        let mut nodes = self.nodes.iter();
+        let (root, n_children) = nodes.next().ok_or(SnapshotError::Empty)?;
+        let mut tree = Tree::with_capacity(root.clone(), self.nodes.len());
+        // The nodes waiting for their children, with the number of children left to add.
+        let mut parents: Vec<(NodeId, usize)> = vec![(tree.root().id(), *n_children)];
+        for (value, n_children) in nodes {
+            while parents.last().ok_or(SnapshotError::TooManyNodes)?.1 == 0 {
+                parents.pop();
+            }
+            let parent = parents.last_mut().unwrap();
+            parent.1 -= 1;
+            let id = tree.get_mut(parent.0).unwrap().append(value.clone()).id();
+            parents.push((id, *n_children));
+        }
+        if parents.iter().any(|&(_, left)| left > 0) {
+            return Err(SnapshotError::MissingNodes);
+        }
+        Ok(tree)
+    }
+}
+
+impl<N: Serialize + DeserializeOwned> TreeSnapshot<N> {
+    /// Compact binary encoding of the snapshot, with bincode.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        bincode::serialize(self)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        bincode::deserialize(bytes)
+    }
+}
+
+#[test]
+fn test_snapshot_round_trip() {
+    use crate::test_games::Race;
+    use crate::{DefaultMcts, DefaultPlayout};
+
+    let game = Race(0);
+    let mut mcts = DefaultMcts::new(&game);
+    for _ in 0..200 {
+        mcts.execute(&1., ());
+    }
+    let snapshot = mcts.snapshot();
+    let decoded = TreeSnapshot::from_bytes(&snapshot.to_bytes().unwrap()).unwrap();
+    assert_eq!(snapshot, decoded);
+
+    let mut resumed = DefaultMcts::from_snapshot(&game, &decoded, DefaultPlayout).unwrap();
+    assert_eq!(resumed.memory_usage().nodes, mcts.memory_usage().nodes);
+    let paths = |tree: &Tree<_>| -> Vec<Vec<u8>> {
+        tree.root().descendants().map(|n| crate::move_path(tree, n.id())).collect()
+    };
+    assert_eq!(paths(resumed.tree()), paths(mcts.tree()));
+    resumed.execute(&1., ());
+    assert_eq!(resumed.tree().root().value().n_visits, 201);
+}
+
+#[test]
+fn test_malformed_snapshots_are_rejected() {
+    use crate::test_games::{Choice, Race};
+    use crate::{DefaultMcts, DefaultPlayout};
+
+    let game = Race(0);
+    let mut mcts = DefaultMcts::new(&game);
+    for _ in 0..20 {
+        mcts.execute(&1., ());
+    }
+    let mut snapshot = mcts.snapshot();
+    let other = Choice(None);
+    let error = DefaultMcts::from_snapshot(&other, &snapshot, DefaultPlayout).unwrap_err();
+    assert_eq!(error, SnapshotError::RootMismatch);
+    snapshot.nodes.pop();
+    assert_eq!(snapshot.to_tree().unwrap_err(), SnapshotError::MissingNodes);
+    let root = snapshot.nodes[0].0.clone();
+    snapshot.nodes = vec![(root.clone(), 0), (root, 0)];
+    assert_eq!(snapshot.to_tree().unwrap_err(), SnapshotError::TooManyNodes);
+    snapshot.nodes.clear();
+    assert_eq!(snapshot.to_tree().unwrap_err(), SnapshotError::Empty);
+}
@@ -0,0 +1,195 @@
+use std::marker::PhantomData;
+
+use ego_tree::{NodeId, Tree};
+use noisy_float::prelude::{n64, Float};
+use rand::prelude::SliceRandom;
+
+use crate::aliases::{Nat, Num, PlayedMoves};
+use crate::mcts_node::MctsNode;
+use crate::rng::search_rng;
+use crate::traits::{BackPropPolicy, Evaluator, Playout, SinglePlayerGame};
+
+/// Additional info of the nodes of a Single-Player MCTS.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpMctsInfo {
+    /// The best score of the playouts which went through the node.
+    pub best_score: f64,
+}
+
+impl Default for SpMctsInfo {
+    fn default() -> Self {
+        Self {
+            best_score: f64::NEG_INFINITY,
+        }
+    }
+}
+
+/// Arguments of the `SpMctsEvaluator`.
+#[derive(Clone, Copy, Debug)]
+pub struct SpMctsArgs {
+    /// The exploration constant.
+    pub c: f64,
+    /// Added to the variance, so the nodes with a low variance but few visits are still explored.
+    pub d: f64,
+    /// If true the best score of a node is used instead of the mean of its scores (max-backup).
+    pub max_backup: bool,
+}
+
+/// Evaluates the nodes with the SP-MCTS formula:
+/// mean + c * sqrt(ln(parent visits) / visits) + sqrt((sum squared rewards - visits * mean^2 + d)
+/// / visits), and the final states with their score.
+pub struct SpMctsEvaluator;
+
+impl<State: SinglePlayerGame> Evaluator<State, f64, SpMctsInfo> for SpMctsEvaluator {
+    type Args = SpMctsArgs;
+    type EvalResult = f64;
+
+    fn eval_child<T: Clone>(
+        child: &MctsNode<T, State::Move, f64, SpMctsInfo>,
+        _turn: &State::Player,
+        parent_visits: Nat,
+        args: &Self::Args,
+    ) -> Num {
+        let n_visits = child.n_visits as f64;
+        let mean = child.sum_rewards / n_visits;
+        let exploitation = if args.max_backup {
+            child.additional_info.best_score
+        } else {
+            mean
+        };
+        let exploration = (n64(parent_visits as f64).ln() / n_visits).sqrt();
+        let variance = (child.sum_squared_rewards - n_visits * mean * mean + args.d) / n_visits;
+        n64(exploitation) + n64(args.c) * exploration + n64(variance.max(0.)).sqrt()
+    }
+
+    fn evaluate_leaf(child: &State, _turn: &State::Player) -> Self::EvalResult {
+        child.score()
+    }
+}
+
+/// Backpropagates the score like the default backprop, and also the best score.
+pub struct SpMctsBackProp;
+
+impl<T: Clone, Move: Clone> BackPropPolicy<T, Move, f64, SpMctsInfo> for SpMctsBackProp {
+    fn backprop(tree: &mut Tree<MctsNode<T, Move, f64, SpMctsInfo>>, leaf: NodeId, score: f64) {
+        let mut current_node_id = Some(leaf);
+        while let Some(node_id) = current_node_id {
+            let mut node_to_update = tree.get_mut(node_id).unwrap();
+            let node = node_to_update.value();
+            node.n_visits += 1;
+            node.sum_rewards += score;
+            node.sum_squared_rewards += score * score;
+            node.additional_info.best_score = node.additional_info.best_score.max(score);
+            current_node_id = node_to_update.parent().map(|p| p.id());
+        }
+    }
+}
+
+/// Simulating taking random moves until the end, and recording the best sequence of moves found
+/// during the search, from the root to the end of the playout.
+pub struct SpMctsPlayout<T: SinglePlayerGame> {
+    best_sequence: Vec<T::Move>,
+    best_score: f64,
+    phantom_state: PhantomData<T>,
+}
+
+impl<T: SinglePlayerGame> SpMctsPlayout<T> {
+    /// The best sequence of moves found and its score, the sequence is empty before the first
+    /// playout.
+    pub fn best_sequence(&self) -> (&[T::Move], f64) {
+        (&self.best_sequence, self.best_score)
+    }
+}
+
+impl<T: SinglePlayerGame> Default for SpMctsPlayout<T> {
+    fn default() -> Self {
+        Self {
+            best_sequence: vec![],
+            best_score: f64::NEG_INFINITY,
+            phantom_state: PhantomData,
+        }
+    }
+}
+
+impl<T: SinglePlayerGame> Playout<T, f64> for SpMctsPlayout<T> {
+    type Args = ();
+
+    fn playout(&mut self, mut state: T, _args: ()) -> (T, PlayedMoves<T>) {
+        let mut played = vec![];
+        while !state.is_final() {
+            let moves = state.legals_moves();
+            let m = moves.choose(&mut search_rng()).unwrap();
+            played.push((state.player_turn(), m.clone()));
+            state.do_move(m);
+        }
+        (state, played)
+    }
+
+    fn backprop(&mut self, historic: &[T::Move], moves: &[(T::Player, T::Move)], score: &f64) {
+        if *score > self.best_score {
+            self.best_score = *score;
+            self.best_sequence = historic.to_vec();
+            self.best_sequence.extend(moves.iter().map(|(_, m)| m.clone()));
+        }
+    }
+}
+
+#[test]
+fn test_sp_mcts_records_best_sequence() {
+    use crate::traits::{GameTrait, Outcome};
+    use crate::SpMcts;
+
+    /// Three numbers between 0 and 3 are chosen, the score is their sum if they are all different.
+    #[derive(Clone)]
+    struct Distinct(Vec<u8>);
+
+    impl GameTrait for Distinct {
+        type Player = ();
+        type Move = u8;
+
+        fn legals_moves(&self) -> Vec<u8> {
+            (0..4).collect()
+        }
+
+        fn player_turn(&self) {}
+
+        fn hash(&self) -> u64 {
+            0
+        }
+
+        fn is_final(&self) -> bool {
+            self.0.len() == 3
+        }
+
+        fn do_move(&mut self, m: &u8) {
+            self.0.push(*m)
+        }
+
+        fn outcome(&self) -> Outcome<()> {
+            Outcome::Scores(vec![((), self.score())])
+        }
+    }
+
+    impl SinglePlayerGame for Distinct {
+        fn score(&self) -> f64 {
+            let distinct = self.0.iter().all(|x| self.0.iter().filter(|&y| y == x).count() == 1);
+            if distinct {
+                self.0.iter().map(|&x| x as f64).sum()
+            } else {
+                0.
+            }
+        }
+    }
+
+    let puzzle = Distinct(vec![]);
+    let mut mcts = SpMcts::new(&puzzle);
+    let args = SpMctsArgs { c: 6., d: 1., max_backup: true };
+    for _ in 0..500 {
+        mcts.execute(&args, ());
+    }
+    let (sequence, score) = mcts.playout_policy().best_sequence();
+    assert_eq!(score, 6.);
+    assert_eq!(sequence.iter().map(|&x| x as usize).sum::<usize>(), 6);
+    assert_eq!(mcts.tree().root().value().additional_info.best_score, 6.);
+}
@@ -0,0 +1,94 @@
+use std::time::Duration;
+
+/// Statistics of a search, accumulated over all its iterations.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SearchStats {
+    pub iterations: usize,
+    pub nodes_created: usize,
+    /// The sum of the depths of the nodes the playouts started from.
+    pub total_selection_depth: usize,
+    pub max_selection_depth: usize,
+    /// The number of playouts started at each depth.
+    pub depth_histogram: Vec<usize>,
+    /// The average number of children of the nodes having children, computed from the tree.
+    pub branching_factor: f64,
+    pub total_playout_length: usize,
+    /// Time spent in the selection and the expansion. The times are only measured when the
+    /// timing of the search is enabled, see `LazyMcts::set_timing`.
+    pub tree_policy_time: Duration,
+    /// Time spent in the playouts and their evaluation.
+    pub playout_time: Duration,
+    pub backprop_time: Duration,
+}
+
+impl SearchStats {
+    pub fn average_selection_depth(&self) -> f64 {
+        self.total_selection_depth as f64 / self.iterations.max(1) as f64
+    }
+
+    pub fn average_playout_length(&self) -> f64 {
+        self.total_playout_length as f64 / self.iterations.max(1) as f64
+    }
+
+    /// Time spent in the three phases of the iterations.
+    pub fn total_time(&self) -> Duration {
+        self.tree_policy_time + self.playout_time + self.backprop_time
+    }
+
+    /// 0 if no time was measured.
+    pub fn iterations_per_second(&self) -> f64 {
+        self.per_second(self.iterations)
+    }
+
+    /// 0 if no time was measured.
+    pub fn nodes_per_second(&self) -> f64 {
+        self.per_second(self.nodes_created)
+    }
+
+    fn per_second(&self, count: usize) -> f64 {
+        let seconds = self.total_time().as_secs_f64();
+        if seconds > 0. {
+            count as f64 / seconds
+        } else {
+            0.
+        }
+    }
+
+    /// Records an iteration whose playout started at `depth` and lasted `playout_length` moves.
+    pub(crate) fn add_iteration(&mut self, depth: usize, playout_length: usize, expanded: bool) {
+        self.iterations += 1;
+        self.nodes_created += expanded as usize;
+        self.total_selection_depth += depth;
+        self.max_selection_depth = self.max_selection_depth.max(depth);
+        if self.depth_histogram.len() <= depth {
+            self.depth_histogram.resize(depth + 1, 0);
+        }
+        self.depth_histogram[depth] += 1;
+        self.total_playout_length += playout_length;
+    }
+}
+
+#[test]
+fn test_search_stats() {
+    use crate::test_games::Race;
+    use crate::DefaultMcts;
+
+    let game = Race(0);
+    let mut mcts = DefaultMcts::new(&game);
+    mcts.execute(&1., ());
+    assert_eq!(mcts.stats().total_time(), Duration::ZERO);
+    assert_eq!(mcts.stats().iterations_per_second(), 0.);
+    mcts.set_timing(true);
+    for _ in 1..100 {
+        mcts.execute(&1., ());
+    }
+    let stats = mcts.stats();
+    assert_eq!(stats.iterations, 100);
+    assert_eq!(stats.nodes_created + 1, mcts.tree().nodes().count());
+    assert_eq!(stats.depth_histogram.iter().sum::<usize>(), 100);
+    assert_eq!(stats.depth_histogram.len(), stats.max_selection_depth + 1);
+    assert!(stats.average_selection_depth() >= 1.);
+    assert!(stats.average_playout_length() > 0.);
+    assert!(stats.branching_factor > 1. && stats.branching_factor <= 3.);
+    assert!(stats.total_time() > Duration::ZERO && stats.iterations_per_second() > 0.);
+}
@@ -0,0 +1,181 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::aliases::{MctsTree, Nat};
+use crate::reward::PlayerReward;
+
+/// When to stop a search before the end of its budget.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum StoppingRule {
+    /// The whole budget is used.
+    #[default]
+    Never,
+    /// Stops when the most visited child of the root has more visits than any other child could
+    /// reach in the remaining iterations.
+    MostVisited,
+    /// Stops when the lower confidence bound of the best mean reward of the root player is above
+    /// the upper confidence bound of every other child, the bounds being `mean ± z * sqrt(variance
+    /// / visits)`. Every child needs `min_visits` visits before stopping.
+    Confidence { z: f64, min_visits: Nat },
+}
+
+/// Summary of a search.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SearchReport {
+    /// The iterations executed.
+    pub iterations: usize,
+    /// The iterations of the budget not executed because of the stopping rule.
+    pub iterations_saved: usize,
+    /// The nodes pruned to respect the memory limit of the search.
+    pub nodes_pruned: usize,
+    /// The search was interrupted by its cancellation token.
+    pub cancelled: bool,
+}
+
+/// Stops searches from another thread. The token is shared by cloning it, every clone sees the
+/// cancellation, so a single token can interrupt the threads of a parallel search. The searches
+/// check it between two iterations and keep the best move found so far.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Allows the searches using the token to run again.
+    pub fn reset(&self) {
+        self.0.store(false, Ordering::Relaxed);
+    }
+}
+
+impl StoppingRule {
+    /// Returns true if the search of the root player can stop with `remaining` iterations left.
+    pub fn should_stop<T, Move, Player, R, A>(
+        &self,
+        tree: &MctsTree<T, Move, R, A>,
+        root_player: &Player,
+        remaining: usize,
+    ) -> bool
+        where
+            T: Clone,
+            Move: Clone,
+            R: PlayerReward<Player> + Clone,
+            A: Clone + Default,
+    {
+        let root = tree.root();
+        if !root.has_children() {
+            return false;
+        }
+        match *self {
+            StoppingRule::Never => false,
+            StoppingRule::MostVisited => {
+                let mut visits: Vec<usize> = root
+                    .children()
+                    .map(|c| c.value().n_visits as usize)
+                    .collect();
+                // The children not expanded yet have no visits.
+                if root.value().can_add_child() {
+                    visits.push(0);
+                }
+                visits.sort_unstable_by(|a, b| b.cmp(a));
+                visits.len() == 1 || visits[0] > visits[1] + remaining
+            }
+            StoppingRule::Confidence { z, min_visits } => {
+                if root.value().can_add_child()
+                    || root.children().any(|c| c.value().n_visits < min_visits.max(1)) {
+                    return false;
+                }
+                let bounds: Vec<(f64, f64)> = root
+                    .children()
+                    .map(|c| {
+                        let node = c.value();
+                        let n = node.n_visits as f64;
+                        let mean = node.sum_rewards.reward_of(root_player) / n;
+                        let squared_mean = node.sum_squared_rewards.reward_of(root_player) / n;
+                        let radius = z * ((squared_mean - mean * mean).max(0.) / n).sqrt();
+                        (mean - radius, mean + radius)
+                    })
+                    .collect();
+                let (best, &(lower, _)) = bounds
+                    .iter()
+                    .enumerate()
+                    .max_by(|(_, a), (_, b)| {
+                        (a.0 + a.1).partial_cmp(&(b.0 + b.1)).unwrap()
+                    })
+                    .unwrap();
+                bounds
+                    .iter()
+                    .enumerate()
+                    .all(|(i, &(_, upper))| i == best || lower > upper)
+            }
+        }
+    }
+}
+
+#[test]
+fn test_most_visited_rule_saves_iterations() {
+    use crate::test_games::Choice;
+    use crate::mcts_uct_agent_with_stopping;
+
+    let (m, report) =
+        mcts_uct_agent_with_stopping(&Choice(None), 1000, 0.1, &StoppingRule::MostVisited);
+    assert_eq!(m, 1);
+    assert!(report.iterations_saved > 0);
+    assert_eq!(report.iterations + report.iterations_saved, 1000);
+}
+
+#[test]
+fn test_cancelled_search_returns_the_best_move_so_far() {
+    use crate::test_games::Choice;
+    use crate::mcts_uct_agent_cancellable;
+    use std::thread;
+    use std::time::Duration;
+
+    let token = CancellationToken::new();
+    let canceller = token.clone();
+    let handle = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(20));
+        canceller.cancel();
+    });
+    let (m, report) = mcts_uct_agent_cancellable(&Choice(None), usize::MAX, 0.1, &token);
+    handle.join().unwrap();
+    assert_eq!(m, Some(1));
+    assert!(report.cancelled);
+    assert!(report.iterations > 0);
+    // The iterations lost to the cancellation aren't saved by a stopping rule.
+    assert_eq!(report.iterations_saved, 0);
+
+    let (m, report) = mcts_uct_agent_cancellable(&Choice(None), 1000, 0.1, &token);
+    assert_eq!(m, None);
+    assert_eq!((report.iterations, report.iterations_saved, report.cancelled), (0, 0, true));
+}
+
+#[test]
+fn test_confidence_rule_stops_once_the_best_move_is_separated() {
+    use crate::test_games::Guess;
+    use crate::{mcts_uct_agent_with_stopping, DefaultMcts};
+
+    let rule = StoppingRule::Confidence { z: 2., min_visits: 5 };
+    let (m, report) = mcts_uct_agent_with_stopping(&Guess(None), 10_000, 1., &rule);
+    assert_eq!(m, 5);
+    assert!(report.iterations_saved > 0);
+    assert_eq!(report.iterations + report.iterations_saved, 10_000);
+
+    // The rule doesn't stop before every child has `min_visits` visits.
+    let game = Guess(None);
+    let mut mcts = DefaultMcts::new(&game);
+    while !rule.should_stop(mcts.tree(), &0, usize::MAX) {
+        mcts.execute(&1., ());
+    }
+    assert!(mcts.tree().root().children().all(|c| c.value().n_visits >= 5));
+    assert_eq!(mcts.tree().root().children().count(), 8);
+}
@@ -0,0 +1,118 @@
+use crate::traits::{GameTrait, HeuristicGame, Outcome, UndoableGame};
+
+/// Each player adds 1, 2 or 3 to the total until it reaches 20. The player to move is the parity
+/// of the total, and the player who isn't to move at the end wins.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Race(pub u8);
+
+impl GameTrait for Race {
+    type Player = u8;
+    type Move = u8;
+
+    fn legals_moves(&self) -> Vec<u8> {
+        vec![1, 2, 3]
+    }
+
+    fn player_turn(&self) -> u8 {
+        self.0 % 2
+    }
+
+    fn hash(&self) -> u64 {
+        0
+    }
+
+    fn is_final(&self) -> bool {
+        self.0 >= 20
+    }
+
+    fn do_move(&mut self, m: &u8) {
+        self.0 += m
+    }
+
+    fn outcome(&self) -> Outcome<u8> {
+        Outcome::Win(1 - self.0 % 2)
+    }
+}
+
+impl UndoableGame for Race {
+    fn undo_move(&mut self, m: &u8) {
+        self.0 -= m
+    }
+}
+
+/// The first player is always estimated to win 3 times out of 4.
+impl HeuristicGame for Race {
+    fn heuristic(&self, player: &u8) -> f64 {
+        if *player == 0 {
+            0.75
+        } else {
+            0.25
+        }
+    }
+}
+
+/// The first player chooses 0 or 1 and wins only by choosing 1. Its hash is the largest one.
+#[derive(Clone, Debug)]
+pub(crate) struct Choice(pub Option<u8>);
+
+impl GameTrait for Choice {
+    type Player = u8;
+    type Move = u8;
+
+    fn legals_moves(&self) -> Vec<u8> {
+        vec![0, 1]
+    }
+
+    fn player_turn(&self) -> u8 {
+        0
+    }
+
+    fn hash(&self) -> u64 {
+        u64::MAX
+    }
+
+    fn is_final(&self) -> bool {
+        self.0.is_some()
+    }
+
+    fn do_move(&mut self, m: &u8) {
+        self.0 = Some(*m)
+    }
+
+    fn outcome(&self) -> Outcome<u8> {
+        Outcome::Win(if self.0 == Some(1) { 0 } else { 1 })
+    }
+}
+
+/// The first player chooses a number between 0 and 7, and wins only by choosing 5.
+#[derive(Clone, Debug)]
+pub(crate) struct Guess(pub Option<u8>);
+
+impl GameTrait for Guess {
+    type Player = u8;
+    type Move = u8;
+
+    fn legals_moves(&self) -> Vec<u8> {
+        (0..8).collect()
+    }
+
+    fn player_turn(&self) -> u8 {
+        0
+    }
+
+    fn hash(&self) -> u64 {
+        0
+    }
+
+    fn is_final(&self) -> bool {
+        self.0.is_some()
+    }
+
+    fn do_move(&mut self, m: &u8) {
+        self.0 = Some(*m)
+    }
+
+    fn outcome(&self) -> Outcome<u8> {
+        Outcome::Win(if self.0 == Some(5) { 0 } else { 1 })
+    }
+}
@@ -2,10 +2,22 @@ use std::fmt::Debug;
 
 use ego_tree::{NodeId, Tree};
 
-use crate::aliases::{LazyMctsNode, LazyMctsTree, Num};
-use crate::mcts_node::MctsNode;
+use crate::aliases::{EagerMctsTree, LazyMctsTree, Num, PlayedMoves};
+use crate::defaults::select_leaf_by;
+use crate::mcts_node::{move_path, MctsNode};
 use crate::Nat;
 
+/// The result of a finished game.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Outcome<Player> {
+    /// The game has a single winner.
+    Win(Player),
+    /// Nobody won.
+    Draw,
+    /// The final score of each player, the higher the better.
+    Scores(Vec<(Player, f64)>),
+}
+
 pub trait GameTrait: Clone {
     type Player: Debug + Clone + Eq;
     type Move: Debug + Clone;
@@ -25,32 +37,102 @@ pub trait GameTrait: Clone {
     /// Play the move and alter the state.
     fn do_move(&mut self, m: &Self::Move);
 
-    /// If the game is finished this function returns the winner of the game.
-    fn get_winner(&self) -> Self::Player;
+    /// If the game is finished this function returns its outcome.
+    fn outcome(&self) -> Outcome<Self::Player>;
+
+    /// If the game is finished and won by a single player, returns the winner, see `outcome`.
+    fn get_winner(&self) -> Option<Self::Player> {
+        match self.outcome() {
+            Outcome::Win(player) => Some(player),
+            _ => None,
+        }
+    }
+
+    /// The lowest and highest final scores possible. When known, the rewards of a game finished
+    /// with `Outcome::Scores` are the normalized margins of victory instead of win/draw/loss.
+    fn score_bounds(&self) -> Option<(f64, f64)> {
+        None
+    }
+}
+
+/// A game whose moves can be undone, the search can then play the moves of each iteration on a
+/// single working state instead of cloning the root state, see `LazyMcts::execute_with_undo`.
+pub trait UndoableGame: GameTrait {
+    /// Undoes `m`, which is the last move played on the state.
+    fn undo_move(&mut self, m: &Self::Move);
+}
+
+/// A single player game, like a puzzle or an optimization problem, its final states are evaluated
+/// with a score to maximize instead of a winner.
+pub trait SinglePlayerGame: GameTrait {
+    /// The score of a final state, the higher the better.
+    fn score(&self) -> f64;
+}
+
+/// A game with an heuristic estimating the final reward of a state, used to stop the playouts
+/// before the end of the game.
+pub trait HeuristicGame: GameTrait {
+    /// Estimated reward of the player, in [0, 1].
+    fn heuristic(&self, player: &Self::Player) -> f64;
+}
+
+/// Scores the moves of a state, the higher the better. Used to guide the playouts.
+pub trait MoveScorer<State: GameTrait> {
+    fn score(state: &State, m: &State::Move) -> f64;
 }
 
 pub trait Evaluator<State: GameTrait, Reward: Clone, AdditionalInfo: Clone + Default> {
     type Args;
     type EvalResult: Clone;
 
-    /// Evaluates each node of the monte carlo tree search.
+    /// Evaluates each node of the monte carlo tree search, lazy or eager, from the point of view
+    /// of `turn` the player choosing between the children.
     /// for ex: using UCT
-    fn eval_child(
-        child: &LazyMctsNode<State, Reward, AdditionalInfo>,
+    fn eval_child<T: Clone>(
+        child: &MctsNode<T, State::Move, Reward, AdditionalInfo>,
         turn: &State::Player,
         parent_visits: Nat,
         args: &Self::Args,
     ) -> Num;
 
     /// Evaluates the a final state, when a simulation is over when call this function to know
-    /// the reward.
-    fn evaluate_leaf(child: State, turn: &State::Player) -> Self::EvalResult;
+    /// the reward. `turn` is the player to move at the root of the search.
+    fn evaluate_leaf(child: &State, turn: &State::Player) -> Self::EvalResult;
+
+    /// The value of the moves not expanded yet, the default tree policies go down to the best
+    /// child instead of expanding a move when the child has a greater value. `None`, the default,
+    /// expands every move of a node before going deeper.
+    fn first_play_urgency(_args: &Self::Args) -> Option<Num> {
+        None
+    }
 }
 
-pub trait Playout<State> {
+pub trait Playout<State: GameTrait, EvalResult = ()> {
     type Args;
-    /// Plays the state to have a final state.
-    fn playout(state: State, args: Self::Args) -> State;
+    /// Plays the state to have a final state, returns it with the moves played and their players.
+    fn playout(&mut self, state: State, args: Self::Args) -> (State, PlayedMoves<State>);
+
+    /// Same as `playout`, knowing the historic of moves leading to the state. The searches call
+    /// this one, by default the historic is ignored.
+    fn playout_from(
+        &mut self,
+        _historic: &[State::Move],
+        state: State,
+        args: Self::Args,
+    ) -> (State, PlayedMoves<State>) {
+        self.playout(state, args)
+    }
+
+    /// Called with the historic of the node the playout started from, the moves of the playout and
+    /// their evaluation, it allows the playout policy to learn during the search. Does nothing by
+    /// default.
+    fn backprop(
+        &mut self,
+        _historic: &[State::Move],
+        _moves: &[(State::Player, State::Move)],
+        _eval: &EvalResult,
+    ) {
+    }
 }
 
 pub trait LazyTreePolicy<
@@ -66,8 +148,34 @@ pub trait LazyTreePolicy<
         tree: &mut LazyMctsTree<State, Reward, A>,
         root_state: State,
         evaluator_args: &EV::Args,
+    ) -> (NodeId, State) {
+        let root_id = tree.root().id();
+        Self::tree_policy_from(tree, root_id, root_state, evaluator_args)
+    }
+
+    /// Same as `tree_policy` but the selection starts from the node `start` instead of the root,
+    /// used by the root strategies choosing themselves the child of the root to explore.
+    fn tree_policy_from(
+        tree: &mut LazyMctsTree<State, Reward, A>,
+        start: NodeId,
+        root_state: State,
+        evaluator_args: &EV::Args,
     ) -> (NodeId, State);
 
+    /// Selection without expansion, used when the tree can't grow anymore. Goes down from
+    /// `start` choosing the best child at each node until a leaf, and returns it with its state.
+    fn select_leaf(
+        tree: &LazyMctsTree<State, Reward, A>,
+        start: NodeId,
+        root_state: State,
+        evaluator_args: &EV::Args,
+    ) -> (NodeId, State) {
+        let state = Self::update_state(root_state, &move_path(tree, start));
+        select_leaf_by(tree, start, state, |tree, turn, parent_id| {
+            Self::best_child(tree, turn, parent_id, evaluator_args)
+        })
+    }
+
     /// This method is only needed because we don't store the state in each node so we need, to
     /// update the state with the stored historic in each node before simulating or expanding it.
     fn update_state(mut root_state: State, historic: &[State::Move]) -> State {
@@ -86,6 +194,62 @@ pub trait LazyTreePolicy<
     ) -> NodeId;
 }
 
+/// The tree policy of an `EagerMcts`, the states are read from the tree instead of being replayed
+/// from the root.
+pub trait EagerTreePolicy<
+    State: GameTrait,
+    EV: Evaluator<State, Reward, A>,
+    A: Clone + Default,
+    Reward: Clone,
+>
+{
+    /// Selects a node and expands it, returns the new node with its state. The states of the new
+    /// nodes are cached every `cache_interval` plies.
+    fn tree_policy(
+        tree: &mut EagerMctsTree<State, Reward, A>,
+        cache_interval: usize,
+        evaluator_args: &EV::Args,
+    ) -> (NodeId, State);
+
+    /// This method use the Evaluator to get best child using evaluate_child.
+    fn best_child(
+        tree: &EagerMctsTree<State, Reward, A>,
+        turn: &State::Player,
+        parent_id: NodeId,
+        evaluator_args: &EV::Args,
+    ) -> NodeId;
+}
+
+/// Observes the phases of the iterations of a search, to collect metrics or debug. Every method
+/// does nothing by default, and `()` is the observer doing nothing.
+pub trait SearchObserver<State: GameTrait, EvalResult> {
+    /// Called with the path of nodes from the node the selection started from, usually the
+    /// root, to the selected node.
+    fn on_selection(&mut self, _path: &[NodeId]) {}
+
+    /// Called with the node created by the expansion, if any.
+    fn on_expansion(&mut self, _node: NodeId) {}
+
+    /// Called with the moves of the playout.
+    fn on_playout(&mut self, _moves: &[(State::Player, State::Move)]) {}
+
+    /// Called with the evaluation of the final state of the playout.
+    fn on_evaluation(&mut self, _eval: &EvalResult) {}
+
+    /// Called after the backpropagation from the node the playout started from.
+    fn on_backprop(&mut self, _leaf: NodeId) {}
+
+    /// Every how many iterations of a search `on_progress` is called, never by default.
+    fn progress_interval(&self) -> Option<usize> {
+        None
+    }
+
+    /// Called every `progress_interval` iterations of a search with the current best move.
+    fn on_progress(&mut self, _iterations: usize, _best_move: &State::Move) {}
+}
+
+impl<State: GameTrait, EvalResult> SearchObserver<State, EvalResult> for () {}
+
 pub trait BackPropPolicy<
     State: Clone,
     Move: Clone,
@@ -1,110 +1,534 @@
 use core::fmt;
+use std::borrow::Cow;
 use std::fmt::{Debug, Display, Formatter};
 use std::marker::PhantomData;
-use std::ops::{Add, Div};
 
 use ascii_tree::{Tree, write_tree};
 use ascii_tree::Tree::{Leaf, Node};
 use ego_tree::NodeId;
-use num_traits::{ToPrimitive, Zero};
+use num_traits::Zero;
+use rand_distr::{Distribution, Gumbel};
 
-use crate::aliases::{LazyMctsNode, LazyMctsTree};
+use crate::aliases::{LazyMctsTree, Nat, PlayedMoves};
+#[cfg(feature = "serialize")]
+use crate::aliases::LazyMctsNode;
 use crate::Evaluator;
-use crate::traits::{BackPropPolicy, GameTrait, LazyTreePolicy, Playout};
+use crate::export::{principal_variation, tree_to_dot, tree_to_json, ExportOptions};
+use crate::mcts_node::move_path;
+use crate::memory::{MemoryLimit, MemoryUsage};
+#[cfg(feature = "serialize")]
+use crate::memory::node_bytes;
+use crate::reward::PlayerReward;
+use crate::rng::search_rng;
+use crate::search_core::{SearchCore, SearchPolicies};
+#[cfg(feature = "serialize")]
+use crate::snapshot::{SnapshotError, TreeSnapshot};
+use crate::stats::SearchStats;
+use crate::stopping::{CancellationToken, SearchReport, StoppingRule};
+use crate::traits::{
+    BackPropPolicy, GameTrait, LazyTreePolicy, MoveScorer, Playout, SearchObserver, UndoableGame,
+};
 
-/// This is a special MCTS because it doesn't store the state in the node but instead stores the
-/// historic to the node.
+/// Arguments of the Gumbel root search.
+#[derive(Clone, Copy, Debug)]
+pub struct GumbelArgs {
+    /// The number of root moves sampled without replacement from the priors.
+    pub k: usize,
+    /// With `c_scale`, scales the mean rewards against the priors, the more the children are
+    /// visited the more their mean rewards matter.
+    pub c_visit: f64,
+    pub c_scale: f64,
+}
+
+impl Default for GumbelArgs {
+    fn default() -> Self {
+        Self {
+            k: 16,
+            c_visit: 50.,
+            c_scale: 1.,
+        }
+    }
+}
+
+/// How the move to play is chosen among the children of the root at the end of a search.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum FinalMove {
+    /// The most visited child, the robust child.
+    #[default]
+    MostVisited,
+    /// The child with the highest mean reward for the root player.
+    MaxMeanReward,
+    /// The best child for the tree policy, see `LazyMcts::best_move`.
+    TreePolicy,
+}
+
+/// This is a special MCTS because it doesn't store the state in the node but only the move
+/// leading to it, the historic of moves to the node is rebuilt from its parents, see `move_path`.
 #[derive(Clone)]
 pub struct LazyMcts<'a, State, TP, PP, BP, EV, AddInfo, Reward>
     where
         State: GameTrait,
         TP: LazyTreePolicy<State, EV, AddInfo, Reward>,
-        PP: Playout<State>,
-        BP: BackPropPolicy<Vec<State::Move>, State::Move, Reward, AddInfo, EV::EvalResult>,
+        PP: Playout<State, EV::EvalResult>,
+        BP: BackPropPolicy<Option<State::Move>, State::Move, Reward, AddInfo, EV::EvalResult>,
         EV: Evaluator<State, Reward, AddInfo>,
         AddInfo: Clone + Default,
         Reward: Clone,
 {
-    root_state: &'a State,
+    core: SearchCore<'a, State, Reward, AddInfo>,
     tree_policy: PhantomData<TP>,
-    playout_policy: PhantomData<PP>,
+    playout_policy: PP,
     backprop_policy: PhantomData<BP>,
     evaluator: PhantomData<EV>,
-    tree: LazyMctsTree<State, Reward, AddInfo>,
+}
+
+/// The static policies of a `LazyMcts` with their arguments, `playout_args` gives the arguments
+/// of each playout.
+struct StaticPolicies<'p, TP, PP, BP, EV, EvaluatorArgs, F> {
+    playout_policy: &'p mut PP,
+    evaluator_args: &'p EvaluatorArgs,
+    playout_args: F,
+    phantom: PhantomData<(TP, BP, EV)>,
+}
+
+impl<State, TP, PP, BP, EV, A, R, F> SearchPolicies<State, R, A>
+for StaticPolicies<'_, TP, PP, BP, EV, EV::Args, F>
+    where
+        State: GameTrait,
+        TP: LazyTreePolicy<State, EV, A, R>,
+        PP: Playout<State, EV::EvalResult>,
+        BP: BackPropPolicy<Option<State::Move>, State::Move, R, A, EV::EvalResult>,
+        EV: Evaluator<State, R, A>,
+        A: Clone + Default,
+        R: Clone,
+        F: FnMut() -> PP::Args,
+{
+    type EvalResult = EV::EvalResult;
+
+    fn tree_policy_from(
+        &mut self,
+        tree: &mut LazyMctsTree<State, R, A>,
+        start: NodeId,
+        root_state: State,
+    ) -> (NodeId, State) {
+        TP::tree_policy_from(tree, start, root_state, self.evaluator_args)
+    }
+
+    fn select_leaf(
+        &mut self,
+        tree: &LazyMctsTree<State, R, A>,
+        start: NodeId,
+        root_state: State,
+    ) -> (NodeId, State) {
+        TP::select_leaf(tree, start, root_state, self.evaluator_args)
+    }
+
+    fn playout(&mut self, historic: &[State::Move], state: State) -> (State, PlayedMoves<State>) {
+        self.playout_policy.playout_from(historic, state, (self.playout_args)())
+    }
+
+    fn evaluate_leaf(&self, state: &State, turn: &State::Player) -> EV::EvalResult {
+        EV::evaluate_leaf(state, turn)
+    }
+
+    fn learn(
+        &mut self,
+        historic: &[State::Move],
+        moves: &[(State::Player, State::Move)],
+        eval: &EV::EvalResult,
+    ) {
+        self.playout_policy.backprop(historic, moves, eval)
+    }
+
+    fn backprop(
+        &mut self,
+        tree: &mut LazyMctsTree<State, R, A>,
+        leaf: NodeId,
+        eval: EV::EvalResult,
+    ) {
+        BP::backprop(tree, leaf, eval)
+    }
+
+    fn best_child(
+        &self,
+        tree: &LazyMctsTree<State, R, A>,
+        turn: &State::Player,
+        parent_id: NodeId,
+    ) -> NodeId {
+        TP::best_child(tree, turn, parent_id, self.evaluator_args)
+    }
 }
 
 impl<'a, State, TP, PP, BP, EV, A, R> LazyMcts<'a, State, TP, PP, BP, EV, A, R>
     where
         State: GameTrait,
         TP: LazyTreePolicy<State, EV, A, R>,
-        PP: Playout<State>,
-        BP: BackPropPolicy<Vec<State::Move>, State::Move, R, A, EV::EvalResult>,
+        PP: Playout<State, EV::EvalResult>,
+        BP: BackPropPolicy<Option<State::Move>, State::Move, R, A, EV::EvalResult>,
         EV: Evaluator<State, R, A>,
         A: Clone + Default,
-        R: Clone + Div + ToPrimitive + Zero + Add + Display,
+        R: Clone + Zero + Display,
 {
-    pub fn new(root_state: &'a State) -> Self {
+    pub fn new(root_state: &'a State) -> Self
+        where
+            PP: Default,
+    {
         Self::with_capacity(root_state, 0)
     }
 
-    pub fn with_capacity(root_state: &'a State, capacity: usize) -> Self {
-        let tree = LazyMctsTree::<State, R, A>::with_capacity(
-            LazyMctsNode::<State, R, A> {
-                sum_rewards: Zero::zero(),
-                n_visits: 0,
-                unvisited_moves: root_state.legals_moves(),
-                hash: root_state.hash(),
-                state: vec![],
-                additional_info: Default::default(),
-            },
-            capacity,
-        );
+    pub fn with_capacity(root_state: &'a State, capacity: usize) -> Self
+        where
+            PP: Default,
+    {
+        Self::with_playout_policy(root_state, capacity, PP::default())
+    }
+
+    /// Creates a search owning its root state, it can live as long as needed, for example to
+    /// ponder in another thread.
+    pub fn new_owned(root_state: State) -> Self
+        where
+            PP: Default,
+    {
+        Self::from_root_state(Cow::Owned(root_state), 0, PP::default())
+    }
+
+    /// Creates the search with a playout policy, useful when the policy has a state or parameters.
+    pub fn with_playout_policy(root_state: &'a State, capacity: usize, playout_policy: PP) -> Self {
+        Self::from_root_state(Cow::Borrowed(root_state), capacity, playout_policy)
+    }
+
+    pub(crate) fn from_root_state(
+        root_state: Cow<'a, State>,
+        capacity: usize,
+        playout_policy: PP,
+    ) -> Self {
         Self {
-            root_state,
+            core: SearchCore::new(root_state, capacity),
             tree_policy: PhantomData,
-            playout_policy: PhantomData,
+            playout_policy,
             backprop_policy: PhantomData,
             evaluator: PhantomData,
-            tree,
         }
     }
 
+    /// The policies of the search with their arguments, `playout_args` gives the arguments of
+    /// each playout.
+    fn policies<'p, F: FnMut() -> PP::Args>(
+        playout_policy: &'p mut PP,
+        evaluator_args: &'p EV::Args,
+        playout_args: F,
+    ) -> StaticPolicies<'p, TP, PP, BP, EV, EV::Args, F> {
+        StaticPolicies { playout_policy, evaluator_args, playout_args, phantom: PhantomData }
+    }
+
     /// Executes one selection, expansion?, simulation, backpropagation.
     pub fn execute(&mut self, evaluation_args: &EV::Args, playout_args: PP::Args) {
-        let (node_id, state) =
-            TP::tree_policy(&mut self.tree, self.root_state.clone(), evaluation_args);
-        let final_state = PP::playout(state, playout_args);
-        let eval = EV::evaluate_leaf(final_state, &self.root_state.player_turn());
-        BP::backprop(&mut self.tree, node_id, eval);
+        self.execute_observed(evaluation_args, playout_args, &mut ());
     }
 
-    /// Returns the best move from the root.
-    pub fn best_move(&self, evaluator_args: &EV::Args) -> State::Move {
-        let best_child = TP::best_child(
-            &self.tree,
-            &self.root_state.player_turn(),
-            self.tree.root().id(),
+    /// Same as `execute` but the phases of the iteration are observed by `observer`.
+    pub fn execute_observed<O: SearchObserver<State, EV::EvalResult>>(
+        &mut self,
+        evaluation_args: &EV::Args,
+        playout_args: PP::Args,
+        observer: &mut O,
+    ) {
+        let mut playout_args = Some(playout_args);
+        let mut policies = Self::policies(&mut self.playout_policy, evaluation_args, || {
+            playout_args.take().expect("An iteration has one playout")
+        });
+        self.core.execute_observed(&mut policies, observer);
+    }
+
+    /// Same as `execute` but without cloning the root state: the moves of the iteration are
+    /// played on a working state kept by the search, then undone.
+    pub fn execute_with_undo(&mut self, evaluation_args: &EV::Args, playout_args: PP::Args)
+        where
+            State: UndoableGame,
+    {
+        let mut playout_args = Some(playout_args);
+        let mut policies = Self::policies(&mut self.playout_policy, evaluation_args, || {
+            playout_args.take().expect("An iteration has one playout")
+        });
+        self.core.execute_with_undo(&mut policies);
+    }
+
+    /// The statistics of all the iterations executed.
+    pub fn stats(&self) -> SearchStats {
+        self.core.stats()
+    }
+
+    /// Makes the iterations reproducible: they draw their random numbers from a generator seeded
+    /// with `seed` and owned by the search, whichever thread runs them. Without a seed they use
+    /// the generator of their thread, seeded from entropy.
+    pub fn seed(&mut self, seed: u64) {
+        self.core.seed(seed);
+    }
+
+    /// Times the phases of the iterations in the `stats`, disabled by default since reading the
+    /// clock at each phase slows the small iterations down.
+    pub fn set_timing(&mut self, timing: bool) {
+        self.core.timing = timing;
+    }
+
+    /// Bounds the memory used by the tree, see `MemoryLimit`.
+    pub fn set_memory_limit(&mut self, memory_limit: Option<MemoryLimit>) {
+        self.core.set_memory_limit(memory_limit);
+    }
+
+    pub fn memory_usage(&self) -> MemoryUsage {
+        self.core.memory_usage
+    }
+
+    /// Lets `search` be interrupted from another thread by cancelling `token`, the best move
+    /// found so far is then still available.
+    pub fn set_cancellation_token(&mut self, token: Option<CancellationToken>) {
+        self.core.cancellation_token = token;
+    }
+
+    /// Plays `m` at the root. The subtree of the child reached by `m` becomes the new tree, or the
+    /// tree starts over if `m` was never explored. Returns true if a subtree was kept. The search
+    /// then owns its root state.
+    pub fn advance(&mut self, m: &State::Move) -> bool
+        where
+            State::Move: PartialEq,
+    {
+        self.core.advance(m)
+    }
+
+    /// The state of the root, it changes when the search advances.
+    pub fn root_state(&self) -> &State {
+        &self.core.root_state
+    }
+
+    /// Executes up to `budget` iterations, stopping earlier when `stopping_rule` says the best
+    /// move can't change anymore or when the cancellation token of the search is cancelled.
+    pub fn search(
+        &mut self,
+        budget: usize,
+        stopping_rule: &StoppingRule,
+        evaluator_args: &EV::Args,
+        playout_args: PP::Args,
+    ) -> SearchReport
+        where
+            PP::Args: Clone,
+            R: PlayerReward<State::Player>,
+    {
+        self.search_observed(budget, stopping_rule, evaluator_args, playout_args, &mut ())
+    }
+
+    /// Same as `search` but the iterations are observed by `observer`, which is also given the
+    /// best move every `progress_interval` iterations.
+    pub fn search_observed<O: SearchObserver<State, EV::EvalResult>>(
+        &mut self,
+        budget: usize,
+        stopping_rule: &StoppingRule,
+        evaluator_args: &EV::Args,
+        playout_args: PP::Args,
+        observer: &mut O,
+    ) -> SearchReport
+        where
+            PP::Args: Clone,
+            R: PlayerReward<State::Player>,
+    {
+        let mut policies =
+            Self::policies(&mut self.playout_policy, evaluator_args, || playout_args.clone());
+        self.core.search(&mut policies, budget, stopping_rule, observer)
+    }
+
+    /// Runs Sequential Halving over the children of the root with about `budget` simulations,
+    /// the tree policy is only used below the root. The children are expanded first, then the
+    /// budget is split in rounds, after each round the half of the children with the lowest mean
+    /// rewards are discarded. Each remaining child gets at least one simulation per round, so a
+    /// budget too small for the number of children is exceeded. Returns the surviving move,
+    /// which is also the `best_move` until the next iteration.
+    pub fn sequential_halving(
+        &mut self,
+        budget: usize,
+        evaluator_args: &EV::Args,
+        playout_args: PP::Args,
+    ) -> State::Move
+        where
+            PP::Args: Clone,
+            R: PlayerReward<State::Player>,
+    {
+        let spent = self.expand_root(evaluator_args, &playout_args);
+        let candidates = self.core.tree.root().children().map(|c| (c.id(), 0.)).collect();
+        self.halving(
+            candidates,
+            budget.saturating_sub(spent),
             evaluator_args,
-        );
-        self.tree
-            .get(best_child)
-            .unwrap()
-            .value()
-            .state
-            .last()
-            .expect("The historic of the children of the root is empty, cannot happen")
-            .clone()
+            playout_args,
+            |mean, _| mean,
+        )
+    }
+
+    /// Sequential Halving from Gumbel MuZero: `args.k` moves are sampled without replacement from
+    /// the priors given by `S`, used as logits, thanks to the Gumbel-top-k trick. Then they are
+    /// compared with their Gumbel noise plus their logit plus
+    /// `(c_visit + max visits) * c_scale * mean reward`. Returns the surviving move, which is also
+    /// the `best_move` until the next iteration.
+    pub fn gumbel_sequential_halving<S: MoveScorer<State>>(
+        &mut self,
+        budget: usize,
+        args: &GumbelArgs,
+        evaluator_args: &EV::Args,
+        playout_args: PP::Args,
+    ) -> State::Move
+        where
+            PP::Args: Clone,
+            R: PlayerReward<State::Player>,
+            State::Move: PartialEq,
+    {
+        let gumbel = Gumbel::new(0., 1.).unwrap();
+        let mut moves: Vec<(State::Move, f64)> = self.core.with_own_rng(|core| {
+            core.root_state
+                .legals_moves()
+                .into_iter()
+                .map(|m| {
+                    let score = S::score(&core.root_state, &m) + gumbel.sample(&mut search_rng());
+                    (m, score)
+                })
+                .collect()
+        });
+        moves.sort_by(|a, b| b.1.total_cmp(&a.1));
+        moves.truncate(args.k.max(1));
+
+        // Only the sampled moves are expanded, the others are put back afterwards.
+        let root_id = self.core.tree.root().id();
+        let mut root = self.core.tree.get_mut(root_id).unwrap();
+        let unvisited = &mut root.value().unvisited_moves;
+        let (sampled, others) = unvisited
+            .drain(..)
+            .partition(|m| moves.iter().any(|(sampled, _)| sampled == m));
+        *unvisited = sampled;
+        let spent = self.expand_root(evaluator_args, &playout_args);
+        self.core.tree.root_mut().value().unvisited_moves.extend(others);
+
+        // A sampled move may be left unexpanded, see `expand_root`.
+        let root = self.core.tree.root();
+        let candidates = moves
+            .iter()
+            .filter_map(|(m, score)| {
+                let child = root.children().find(|c| c.value().incoming_move() == Some(m))?;
+                Some((child.id(), *score))
+            })
+            .collect();
+        let scale = args.c_scale;
+        let c_visit = args.c_visit;
+        self.halving(
+            candidates,
+            budget.saturating_sub(spent),
+            evaluator_args,
+            playout_args,
+            |mean, max_visits| (c_visit + max_visits as f64) * scale * mean,
+        )
+    }
+
+    /// Expands the unvisited moves of the root before a root search, even past the memory limit
+    /// since each move must be a candidate. Stops early if an iteration doesn't add a child to
+    /// the root, when the tree policy goes down to an urgent child instead. Returns the number of
+    /// iterations spent.
+    fn expand_root(&mut self, evaluator_args: &EV::Args, playout_args: &PP::Args) -> usize
+        where
+            PP::Args: Clone,
+    {
+        let memory_limit = self.core.memory_limit();
+        self.core.set_memory_limit(None);
+        let mut spent = 0;
+        while self.core.tree.root().value().can_add_child() {
+            let children = self.core.tree.root().children().count();
+            self.execute(evaluator_args, playout_args.clone());
+            spent += 1;
+            if self.core.tree.root().children().count() == children {
+                break;
+            }
+        }
+        self.core.set_memory_limit(memory_limit);
+        spent
+    }
+
+    /// Sequential Halving over `candidates`, children of the root with a prior score. They are
+    /// compared with their prior plus `sigma(mean reward, max visits of the candidates)`.
+    fn halving(
+        &mut self,
+        mut candidates: Vec<(NodeId, f64)>,
+        budget: usize,
+        evaluator_args: &EV::Args,
+        playout_args: PP::Args,
+        sigma: impl Fn(f64, Nat) -> f64,
+    ) -> State::Move
+        where
+            PP::Args: Clone,
+            R: PlayerReward<State::Player>,
+    {
+        let root_player = self.core.root_state.player_turn();
+        let mut policies =
+            Self::policies(&mut self.playout_policy, evaluator_args, || playout_args.clone());
+        let mut remaining = budget;
+        let mut rounds_left = (candidates.len() as f64).log2().ceil().max(1.) as usize;
+        while candidates.len() > 1 {
+            let simulations = (remaining / (rounds_left * candidates.len())).max(1);
+            for &(child_id, _) in &candidates {
+                for _ in 0..simulations {
+                    let root_state = self.core.root_state.clone().into_owned();
+                    self.core.execute_from(&mut policies, child_id, root_state, &mut ());
+                }
+            }
+            remaining = remaining.saturating_sub(simulations * candidates.len());
+            rounds_left = (rounds_left - 1).max(1);
+
+            let tree = &self.core.tree;
+            let max_visits = candidates
+                .iter()
+                .map(|&(id, _)| tree.get(id).unwrap().value().n_visits)
+                .max()
+                .unwrap_or(0);
+            let mut scored: Vec<(NodeId, f64, f64)> = candidates
+                .iter()
+                .map(|&(id, prior)| {
+                    let node = tree.get(id).unwrap().value();
+                    let mean = node.sum_rewards.reward_of(&root_player) / node.n_visits as f64;
+                    (id, prior, prior + sigma(mean, max_visits))
+                })
+                .collect();
+            scored.sort_by(|a, b| b.2.total_cmp(&a.2));
+            scored.truncate((scored.len() + 1) / 2);
+            candidates = scored.into_iter().map(|(id, prior, _)| (id, prior)).collect();
+        }
+        let (survivor, _) = *candidates.first().expect("The root has no legal moves");
+        self.core.root_choice = Some(survivor);
+        self.core.move_to(survivor)
+    }
+
+    /// Returns the best move from the root, or the move chosen by the last root search if no
+    /// iteration happened since.
+    pub fn best_move(&self, evaluator_args: &EV::Args) -> State::Move {
+        self.core.best_move(|tree, turn, root_id| {
+            TP::best_child(tree, turn, root_id, evaluator_args)
+        })
+    }
+
+    /// Returns the move chosen with `criterion`, or the move chosen by the last root search if no
+    /// iteration happened since.
+    pub fn final_move(&self, criterion: FinalMove, evaluator_args: &EV::Args) -> State::Move
+        where
+            R: PlayerReward<State::Player>,
+    {
+        self.core.final_move(criterion, |tree, turn, root_id| {
+            TP::best_child(tree, turn, root_id, evaluator_args)
+        })
     }
 
     pub fn write_tree(&self) -> String {
-        let tree = self.dfs(self.tree.root().id());
+        let tree = self.dfs(self.core.tree.root().id());
         let mut output = String::new();
         write_tree(&mut output, &tree).unwrap();
         output
     }
 
     fn dfs(&self, node_id: NodeId) -> Tree {
-        let node = self.tree.get(node_id).unwrap();
+        let node = self.core.tree.get(node_id).unwrap();
         if node.has_children() {
             let mut nodes = vec![];
             for c in node.children() {
@@ -123,8 +547,95 @@ impl<'a, State, TP, PP, BP, EV, A, R> LazyMcts<'a, State, TP, PP, BP, EV, A, R>
         }
     }
 
+    /// Writes the tree in the DOT format of Graphviz, see `ExportOptions` to limit its size. The
+    /// nodes are labeled with their move, visits, mean reward and evaluation, and the principal
+    /// variation, following the most visited children, is highlighted.
+    pub fn write_dot(&self, options: &ExportOptions, evaluator_args: &EV::Args) -> String
+        where
+            R: PlayerReward<State::Player>,
+    {
+        let root_state = &*self.core.root_state;
+        tree_to_dot::<State, EV, R, A>(&self.core.tree, root_state, options, evaluator_args)
+    }
+
+    /// Writes the subtree of `node_id`, the root for the whole tree, in JSON with a stable format
+    /// documented below. `serialize_info` writes the additional info of a node as a JSON value,
+    /// for example `|_| "null".to_string()`.
+    ///
+    /// ```text
+    /// {"version": 1, "root": NODE}
+    /// NODE = {
+    ///     "move": the move leading to the node formatted with `Debug`, null for the root,
+    ///     "visits": the number of visits,
+    ///     "reward": the sum of the rewards of the player choosing the node, for the root the
+    ///               player to move, null if it isn't finite,
+    ///     "hash": the hash of the node, as a decimal string since it may not fit in a number,
+    ///     "info": the additional info written by `serialize_info`,
+    ///     "children": [NODE, ...]
+    /// }
+    /// ```
+    ///
+    /// The depth of `options` is counted from `node_id`.
+    pub fn write_json(
+        &self,
+        node_id: NodeId,
+        options: &ExportOptions,
+        serialize_info: impl Fn(&A) -> String,
+    ) -> String
+        where
+            R: PlayerReward<State::Player>,
+    {
+        tree_to_json(&self.core.tree, &*self.core.root_state, node_id, options, &serialize_info)
+    }
+
+    /// The moves of the principal variation, following the most visited children from the root.
+    pub fn principal_variation(&self) -> Vec<State::Move> {
+        principal_variation(&self.core.tree)
+            .last()
+            .map_or_else(Vec::new, |&node_id| self.move_path(node_id))
+    }
+
+    /// A copy of the tree which can be serialized, to save the search.
+    #[cfg(feature = "serialize")]
+    pub fn snapshot(&self) -> TreeSnapshot<LazyMctsNode<State, R, A>> {
+        TreeSnapshot::from_tree(&self.core.tree)
+    }
+
+    /// Resumes a saved search, `root_state` must be the root state of the saved search: fails if
+    /// the snapshot is malformed or if the hash of its root isn't the hash of `root_state`.
+    #[cfg(feature = "serialize")]
+    pub fn from_snapshot(
+        root_state: &'a State,
+        snapshot: &TreeSnapshot<LazyMctsNode<State, R, A>>,
+        playout_policy: PP,
+    ) -> Result<Self, SnapshotError> {
+        let tree = snapshot.to_tree()?;
+        if tree.root().value().hash() != root_state.hash() {
+            return Err(SnapshotError::RootMismatch);
+        }
+        let mut mcts = Self::with_playout_policy(root_state, 0, playout_policy);
+        mcts.core.memory_usage = MemoryUsage {
+            nodes: snapshot.nodes.len(),
+            bytes: tree.values().map(node_bytes::<State, R, A>).sum(),
+            pruned_nodes: 0,
+        };
+        mcts.core.tree = tree;
+        Ok(mcts)
+    }
+
+    /// The moves leading from the root to the node.
+    pub fn move_path(&self, node_id: NodeId) -> Vec<State::Move> {
+        move_path(&self.core.tree, node_id)
+    }
+
+    /// The tree of the search. It may still store subtrees pruned by the memory limit, detached
+    /// from the root, so it should be walked from its root.
     pub fn tree(&self) -> &LazyMctsTree<State, R, A> {
-        &self.tree
+        &self.core.tree
+    }
+
+    pub fn playout_policy(&self) -> &PP {
+        &self.playout_policy
     }
 }
 
@@ -132,14 +643,223 @@ impl<State, TP, PP, BP, EV, A, R> Debug for LazyMcts<'_, State, TP, PP, BP, EV,
     where
         State: GameTrait,
         TP: LazyTreePolicy<State, EV, A, R>,
-        PP: Playout<State>,
-        BP: BackPropPolicy<Vec<State::Move>, State::Move, R, A, EV::EvalResult>,
+        PP: Playout<State, EV::EvalResult>,
+        BP: BackPropPolicy<Option<State::Move>, State::Move, R, A, EV::EvalResult>,
         EV: Evaluator<State, R, A>,
         EV::EvalResult: Debug,
         A: Clone + Default + Debug,
-        R: Clone + Debug + Div + Add + Zero + ToPrimitive,
+        R: Clone + Debug,
 {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        f.write_str(&format!("{:?}", self.tree))
+        f.write_str(&format!("{:?}", self.core.tree))
+    }
+}
+
+#[test]
+fn test_sequential_halving_finds_winning_move() {
+    use crate::test_games::Guess;
+    use crate::DefaultMcts;
+
+    struct Uniform;
+
+    impl MoveScorer<Guess> for Uniform {
+        fn score(_state: &Guess, _m: &u8) -> f64 {
+            0.
+        }
+    }
+
+    let game = Guess(None);
+    let mut mcts = DefaultMcts::new(&game);
+    assert_eq!(mcts.sequential_halving(32, &1., ()), 5);
+    assert_eq!(mcts.best_move(&1.), 5);
+
+    let mut mcts = DefaultMcts::new(&game);
+    let args = GumbelArgs { k: 8, ..Default::default() };
+    assert_eq!(mcts.gumbel_sequential_halving::<Uniform>(32, &args, &1., ()), 5);
+    assert_eq!(mcts.best_move(&1.), 5);
+}
+
+#[test]
+fn test_root_searches_expand_the_root_past_a_stop_expanding_limit() {
+    use crate::test_games::Guess;
+    use crate::{DefaultMcts, MemoryBudget, MemoryPolicy};
+
+    struct Uniform;
+
+    impl MoveScorer<Guess> for Uniform {
+        fn score(_state: &Guess, _m: &u8) -> f64 {
+            0.
+        }
+    }
+
+    let game = Guess(None);
+    let limit = MemoryLimit { budget: MemoryBudget::Nodes(1), policy: MemoryPolicy::StopExpanding };
+    let mut mcts = DefaultMcts::new(&game);
+    mcts.set_memory_limit(Some(limit));
+    assert_eq!(mcts.sequential_halving(32, &1., ()), 5);
+    assert_eq!(mcts.tree().root().children().count(), 8);
+
+    let mut mcts = DefaultMcts::new(&game);
+    mcts.set_memory_limit(Some(limit));
+    let args = GumbelArgs { k: 8, ..Default::default() };
+    assert_eq!(mcts.gumbel_sequential_halving::<Uniform>(32, &args, &1., ()), 5);
+    assert_eq!(mcts.tree().root().children().count(), 8);
+}
+
+#[test]
+fn test_gumbel_samples_the_moves_with_the_highest_priors() {
+    use crate::test_games::Guess;
+    use crate::DefaultMcts;
+
+    /// Only 2 and 3 have a chance to be sampled.
+    struct Skewed;
+
+    impl MoveScorer<Guess> for Skewed {
+        fn score(_state: &Guess, m: &u8) -> f64 {
+            if *m == 2 || *m == 3 {
+                100.
+            } else {
+                0.
+            }
+        }
+    }
+
+    let game = Guess(None);
+    let mut mcts = DefaultMcts::new(&game);
+    let args = GumbelArgs { k: 2, ..Default::default() };
+    let survivor = mcts.gumbel_sequential_halving::<Skewed>(32, &args, &1., ());
+    assert!(survivor == 2 || survivor == 3);
+    let mut expanded: Vec<u8> =
+        mcts.tree().root().children().map(|c| *c.value().incoming_move().unwrap()).collect();
+    expanded.sort_unstable();
+    assert_eq!(expanded, vec![2, 3]);
+    assert_eq!(mcts.tree().root().value().unvisited_moves.len(), 6);
+}
+
+#[test]
+fn test_search_from_a_final_state_expands_nothing() {
+    use crate::test_games::Race;
+    use crate::{DefaultEagerMcts, DefaultMcts};
+
+    let game = Race(20);
+    let mut mcts = DefaultMcts::new(&game);
+    mcts.execute(&1., ());
+    assert!(!mcts.tree().root().has_children());
+    assert!(mcts.tree().root().value().unvisited_moves.is_empty());
+
+    let game = Race(18);
+    let mut mcts = DefaultMcts::new(&game);
+    assert!(!mcts.advance(&2));
+    assert!(mcts.tree().root().value().unvisited_moves.is_empty());
+
+    let mut mcts = DefaultEagerMcts::new(Race(20));
+    mcts.execute(&1., ());
+    assert!(!mcts.tree().root().has_children());
+}
+
+#[test]
+fn test_execute_with_undo_restores_the_working_state() {
+    use crate::test_games::Race;
+    use crate::DefaultMcts;
+
+    let game = Race(0);
+    let mut mcts = DefaultMcts::new(&game);
+    for _ in 0..200 {
+        mcts.execute_with_undo(&1., ());
+        assert_eq!(mcts.core.working_state, Some(Race(0)));
+    }
+    assert_eq!(mcts.tree().root().value().n_visits, 200);
+}
+
+#[test]
+fn test_observer_sees_each_phase() {
+    use crate::test_games::Choice;
+    use crate::{DefaultMcts, RewardVec};
+
+    #[derive(Default)]
+    struct Counter {
+        selections: usize,
+        expansions: usize,
+        backprops: usize,
+        progress: Vec<(usize, u8)>,
+    }
+
+    impl SearchObserver<Choice, RewardVec<u8>> for Counter {
+        fn on_selection(&mut self, path: &[NodeId]) {
+            assert_eq!(path.len(), if self.expansions < 2 { 1 } else { 2 });
+            self.selections += 1;
+        }
+
+        fn on_expansion(&mut self, _node: NodeId) {
+            self.expansions += 1;
+        }
+
+        fn on_backprop(&mut self, _leaf: NodeId) {
+            self.backprops += 1;
+        }
+
+        fn progress_interval(&self) -> Option<usize> {
+            Some(10)
+        }
+
+        fn on_progress(&mut self, iterations: usize, best_move: &u8) {
+            self.progress.push((iterations, *best_move));
+        }
+    }
+
+    let game = Choice(None);
+    let mut mcts = DefaultMcts::new(&game);
+    let mut counter = Counter::default();
+    mcts.search_observed(30, &StoppingRule::Never, &1., (), &mut counter);
+    assert_eq!((counter.selections, counter.expansions, counter.backprops), (30, 2, 30));
+    assert_eq!(counter.progress.iter().map(|p| p.0).collect::<Vec<_>>(), vec![10, 20, 30]);
+    assert_eq!(counter.progress.last().unwrap().1, 1);
+}
+
+#[test]
+fn test_execute_passes_the_playout_and_its_evaluation_to_the_playout_policy() {
+    use crate::test_games::Race;
+    use crate::{DefaultBackProp, DefaultLazyTreePolicy, DefaultPlayout, DefaultUctEvaluator};
+    use crate::RewardVec;
+
+    type Call = (Vec<u8>, PlayedMoves<Race>, RewardVec<u8>);
+    type RecordedMcts<'a> = LazyMcts<
+        'a,
+        Race,
+        DefaultLazyTreePolicy<Race, DefaultUctEvaluator, (), RewardVec<u8>>,
+        Recorder,
+        DefaultBackProp,
+        DefaultUctEvaluator,
+        (),
+        RewardVec<u8>,
+    >;
+
+    #[derive(Default)]
+    struct Recorder(Vec<Call>);
+
+    impl Playout<Race, RewardVec<u8>> for Recorder {
+        type Args = ();
+
+        fn playout(&mut self, state: Race, args: ()) -> (Race, PlayedMoves<Race>) {
+            Playout::<Race, ()>::playout(&mut DefaultPlayout, state, args)
+        }
+
+        fn backprop(&mut self, historic: &[u8], moves: &[(u8, u8)], eval: &RewardVec<u8>) {
+            self.0.push((historic.to_vec(), moves.to_vec(), eval.clone()));
+        }
+    }
+
+    let game = Race(0);
+    let mut mcts = RecordedMcts::new(&game);
+    for _ in 0..10 {
+        mcts.execute(&1., ());
+        let (historic, moves, eval) = mcts.playout_policy().0.last().unwrap();
+        let mut state = game.clone();
+        historic.iter().chain(moves.iter().map(|(_, m)| m)).for_each(|m| state.do_move(m));
+        assert!(state.is_final());
+        assert_eq!(*eval, RewardVec::from_outcome(&state.outcome(), None));
     }
+    // Each iteration expands a child of the root, which is the start of the playout.
+    let calls = &mcts.playout_policy().0;
+    assert!(calls[..3].iter().all(|(historic, _, _)| historic.len() == 1));
 }